@@ -0,0 +1,639 @@
+//! Derives resolvers from Anchor `#[derive(Accounts)]` structs.
+//!
+//! Use it through the `derive` feature of `executor-account-resolver-svm`, which re-exports `ResolveAccounts`.
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2, TokenTree};
+use quote::{format_ident, quote, quote_spanned};
+use syn::{
+    parse_macro_input, spanned::Spanned, Attribute, Data, DeriveInput, Fields, GenericArgument,
+    Ident, PathArguments, Type,
+};
+
+/// Generates `resolve_account_metas` and `resolve_instruction` for an Anchor `Accounts` struct.
+///
+/// Each account's address is taken from, in order of precedence:
+///
+/// - `#[resolve(address = <expr>)]`, e.g. `RESOLVER_PUBKEY_KEYPAIR_00` for a new keypair account
+/// - `RESOLVER_PUBKEY_PAYER` for `Signer` accounts
+/// - `#[account(address = <expr>)]`
+/// - `#[account(seeds = [..])]`, derived for `seeds::program` or the resolver program
+/// - the program id of `Program<'info, T>` and the sysvar id of `Sysvar<'info, T>`
+///
+/// Accounts marked `signer`, and `init` or `init_if_needed` accounts without `seeds` or `associated_token::*`,
+/// are signers as in Anchor.
+///
+/// Seeds and addresses may refer to earlier accounts as in Anchor, e.g. `foo.key()` or `foo.data`.
+/// Accounts whose data is read by such a reference must be `Account<'info, T>`s, and are required
+/// from the `remaining_accounts`, stopping the pass with `Resolver::Missing` if they are absent.
+/// Unlike in Anchor, they may not refer to later accounts or to the arguments of `#[instruction(..)]`,
+/// which the resolver does not have, so such accounts need a `#[resolve(address = <expr>)]` override.
+///
+/// The program id defaults to `crate::ID` and can be set with `#[resolve(program_id = <expr>)]` on the struct.
+#[proc_macro_derive(ResolveAccounts, attributes(resolve, account, instruction))]
+pub fn derive_resolve_accounts(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// A `key` or `key = value` item of an `#[account(..)]` or `#[resolve(..)]` attribute,
+/// with any `@ error` suffix of the value removed.
+struct Constraint {
+    key: String,
+    value: Option<TokenStream2>,
+    span: Span,
+}
+
+/// How the address of an account is derived from its type, if at all.
+enum Kind {
+    Signer,
+    /// `Account<'info, T>`, possibly boxed.
+    Account(Type),
+    Program(Type),
+    Sysvar(Type),
+    Other,
+}
+
+struct ResolvedField {
+    ident: Ident,
+    kind: Kind,
+    address: TokenStream2,
+    is_signer: bool,
+    is_writable: bool,
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new(
+            input.span(),
+            "ResolveAccounts can only be derived for structs",
+        ));
+    };
+    let fields: Vec<_> = match &data.fields {
+        Fields::Named(fields) => fields.named.iter().collect(),
+        Fields::Unit => vec![],
+        Fields::Unnamed(fields) => {
+            return Err(syn::Error::new(
+                fields.span(),
+                "ResolveAccounts requires named fields",
+            ))
+        }
+    };
+
+    let mut program_id = quote!(crate::ID);
+    for constraint in constraints(&input.attrs, "resolve")? {
+        match (constraint.key.as_str(), constraint.value) {
+            ("program_id", Some(value)) => program_id = value,
+            _ => {
+                return Err(syn::Error::new(
+                    constraint.span,
+                    "expected `program_id = <expr>`",
+                ))
+            }
+        }
+    }
+
+    let args = instruction_args(&input.attrs)?;
+    let mut resolved = vec![];
+    for field in &fields {
+        let ident = field.ident.clone().unwrap();
+        let account = constraints(&field.attrs, "account")?;
+        let mut address = None;
+        for constraint in constraints(&field.attrs, "resolve")? {
+            match (constraint.key.as_str(), constraint.value) {
+                ("address", Some(value)) => address = Some(value),
+                _ => {
+                    return Err(syn::Error::new(
+                        constraint.span,
+                        "expected `address = <expr>`",
+                    ))
+                }
+            }
+        }
+        let value_of = |key: &str| {
+            account
+                .iter()
+                .find(|constraint| constraint.key == key)
+                .and_then(|constraint| constraint.value.clone())
+        };
+        let kind = kind(&field.ty);
+        let address = match (address, &kind) {
+            (Some(address), _) => address,
+            (None, Kind::Signer) => quote!(::executor_account_resolver_svm::RESOLVER_PUBKEY_PAYER),
+            (None, _) if value_of("address").is_some() => value_of("address").unwrap(),
+            (None, _) if value_of("seeds").is_some() => {
+                let seeds = seeds(value_of("seeds").unwrap(), &ident)?;
+                let program = value_of("seeds::program").unwrap_or(quote!(__program_id));
+                quote!(::anchor_lang::prelude::Pubkey::find_program_address(&[#seeds], &#program).0)
+            }
+            (None, Kind::Program(ty)) => quote!(<#ty as ::anchor_lang::Id>::id()),
+            (None, Kind::Sysvar(ty)) => {
+                quote!(<#ty as ::anchor_lang::solana_program::sysvar::SysvarId>::id())
+            }
+            (None, _) => {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    format!(
+                        "cannot derive the address of `{ident}`, add `#[resolve(address = <expr>)]`"
+                    ),
+                ))
+            }
+        };
+        let has = |key: &str| account.iter().any(|constraint| constraint.key == key);
+        // as in Anchor, accounts initialized without seeds or an associated token address are new keypairs
+        let is_keypair = (has("init") || has("init_if_needed"))
+            && !has("seeds")
+            && !account
+                .iter()
+                .any(|constraint| constraint.key.starts_with("associated_token::"));
+        resolved.push(ResolvedField {
+            is_signer: matches!(kind, Kind::Signer) || has("signer") || is_keypair,
+            is_writable: ["mut", "init", "init_if_needed", "close", "realloc", "zero"]
+                .iter()
+                .any(|key| has(key)),
+            ident,
+            kind,
+            address,
+        });
+    }
+
+    // the bindings are in field order, so an address can only refer to earlier accounts
+    for (index, field) in resolved.iter().enumerate() {
+        let ident = &field.ident;
+        if let Some(arg) = args.iter().find(|arg| refers_to(&field.address, arg)) {
+            return Err(syn::Error::new(
+                ident.span(),
+                format!("the address of `{ident}` refers to the instruction argument `{arg}`, which is not available to the resolver, add `#[resolve(address = <expr>)]`"),
+            ));
+        }
+        if let Some(later) = resolved[index..]
+            .iter()
+            .find(|later| refers_to(&field.address, &later.ident))
+        {
+            return Err(syn::Error::new(
+                ident.span(),
+                format!("the address of `{ident}` refers to `{}`, which is not declared before it, add `#[resolve(address = <expr>)]`", later.ident),
+            ));
+        }
+    }
+
+    let mut bindings = vec![];
+    let mut metas = vec![];
+    for (index, field) in resolved.iter().enumerate() {
+        let ident = &field.ident;
+        let key = format_ident!("__key_{}", ident);
+        let address = &field.address;
+        bindings.push(quote! {
+            let #key: ::anchor_lang::prelude::Pubkey = #address;
+        });
+        let data_needed = resolved[index + 1..]
+            .iter()
+            .any(|later| reads_data(&later.address, ident));
+        if data_needed {
+            let Kind::Account(ty) = &field.kind else {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    format!("the data of `{ident}` is read by a later account, which is only supported for `Account<'info, T>`"),
+                ));
+            };
+            bindings.push(quote_spanned! {ident.span()=>
+                let #ident = ::anchor_lang::prelude::Account::<'__info, #ty>::try_from(
+                    ::executor_account_resolver_svm::require_account(__remaining_accounts, #key)?,
+                )?;
+            });
+        } else {
+            bindings.push(quote! {
+                let #ident = #key;
+            });
+        }
+        let is_signer = field.is_signer;
+        let is_writable = field.is_writable;
+        metas.push(quote! {
+            ::executor_account_resolver_svm::SerializableAccountMeta {
+                pubkey: #key,
+                is_signer: #is_signer,
+                is_writable: #is_writable,
+            }
+        });
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// The account metas of this instruction, with signers replaced by `RESOLVER_PUBKEY_PAYER`.
+            /// Accounts whose data is needed to derive later addresses are required from `remaining_accounts`.
+            #[allow(unused_variables, clippy::useless_conversion)]
+            pub fn resolve_account_metas<'__c: '__info, '__info>(
+                __remaining_accounts: &'__c [::anchor_lang::prelude::AccountInfo<'__info>],
+            ) -> ::executor_account_resolver_svm::ResolveResult<
+                ::std::vec::Vec<::executor_account_resolver_svm::SerializableAccountMeta>,
+            > {
+                use ::anchor_lang::Key as _;
+                let __program_id: ::anchor_lang::prelude::Pubkey = #program_id;
+                #(#bindings)*
+                ::std::result::Result::Ok(::std::vec![#(#metas),*])
+            }
+
+            /// The instruction with the given `data`, e.g. `instruction::Name {}.data()`, and the accounts
+            /// of `resolve_account_metas`.
+            pub fn resolve_instruction<'__c: '__info, '__info>(
+                __remaining_accounts: &'__c [::anchor_lang::prelude::AccountInfo<'__info>],
+                data: ::std::vec::Vec<u8>,
+            ) -> ::executor_account_resolver_svm::ResolveResult<
+                ::executor_account_resolver_svm::SerializableInstruction,
+            > {
+                ::std::result::Result::Ok(::executor_account_resolver_svm::SerializableInstruction {
+                    program_id: #program_id,
+                    accounts: Self::resolve_account_metas(__remaining_accounts)?,
+                    data,
+                })
+            }
+        }
+    })
+}
+
+/// The constraints of every `#[name(..)]` attribute.
+fn constraints(attrs: &[Attribute], name: &str) -> syn::Result<Vec<Constraint>> {
+    let mut constraints = vec![];
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident(name)) {
+        constraints.extend(parse_constraints(attr.meta.require_list()?.tokens.clone())?);
+    }
+    Ok(constraints)
+}
+
+/// Splits `a, b = c, d::e = f @ g` into constraints.
+fn parse_constraints(tokens: TokenStream2) -> syn::Result<Vec<Constraint>> {
+    let mut constraints = vec![];
+    let mut items: Vec<Vec<TokenTree>> = vec![vec![]];
+    for token in tokens {
+        match &token {
+            TokenTree::Punct(punct) if punct.as_char() == ',' => items.push(vec![]),
+            _ => items.last_mut().unwrap().push(token),
+        }
+    }
+    for item in items.into_iter().filter(|item| !item.is_empty()) {
+        let span = item[0].span();
+        let mut key = String::new();
+        let mut tokens = item.into_iter().peekable();
+        while let Some(token) = tokens.peek() {
+            match token {
+                TokenTree::Ident(ident) if key.is_empty() || key.ends_with(':') => {
+                    key.push_str(&ident.to_string())
+                }
+                TokenTree::Punct(punct) if punct.as_char() == ':' => key.push(':'),
+                _ => break,
+            }
+            tokens.next();
+        }
+        let value = match tokens.next() {
+            None => None,
+            Some(TokenTree::Punct(punct)) if punct.as_char() == '=' => Some(
+                tokens
+                    .take_while(
+                        |token| !matches!(token, TokenTree::Punct(punct) if punct.as_char() == '@'),
+                    )
+                    .collect(),
+            ),
+            Some(token) => return Err(syn::Error::new(token.span(), "expected `=` or `,`")),
+        };
+        constraints.push(Constraint { key, value, span });
+    }
+    Ok(constraints)
+}
+
+/// The names of the arguments of an `#[instruction(name: Type, ..)]` attribute.
+fn instruction_args(attrs: &[Attribute]) -> syn::Result<Vec<Ident>> {
+    let mut args = vec![];
+    for attr in attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("instruction"))
+    {
+        let tokens: Vec<TokenTree> = attr
+            .meta
+            .require_list()?
+            .tokens
+            .clone()
+            .into_iter()
+            .collect();
+        // an argument is a name followed by a single `:` at the start of the list or after a comma
+        for (index, token) in tokens.iter().enumerate() {
+            let starts_item = index == 0
+                || matches!(&tokens[index - 1], TokenTree::Punct(punct) if punct.as_char() == ',');
+            match (token, tokens.get(index + 1)) {
+                (TokenTree::Ident(ident), Some(TokenTree::Punct(punct)))
+                    if starts_item
+                        && punct.as_char() == ':'
+                        && punct.spacing() == proc_macro2::Spacing::Alone =>
+                {
+                    args.push(ident.clone())
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(args)
+}
+
+/// Whether `tokens` refer to the variable `ident`, rather than a field, method or path segment of that name.
+fn refers_to(tokens: &TokenStream2, ident: &Ident) -> bool {
+    let tokens: Vec<TokenTree> = tokens.clone().into_iter().collect();
+    tokens.iter().enumerate().any(|(index, token)| match token {
+        TokenTree::Group(group) => refers_to(&group.stream(), ident),
+        TokenTree::Ident(found) if found == ident => {
+            let is_punct = |token: Option<&TokenTree>, c: char| {
+                matches!(token, Some(TokenTree::Punct(punct)) if punct.as_char() == c)
+            };
+            let previous = index.checked_sub(1).map(|index| &tokens[index]);
+            !is_punct(previous, '.')
+                && !is_punct(previous, ':')
+                && !is_punct(tokens.get(index + 1), ':')
+        }
+        _ => false,
+    })
+}
+
+/// The contents of the `[..]` of a `seeds = [..]` constraint.
+fn seeds(value: TokenStream2, ident: &Ident) -> syn::Result<TokenStream2> {
+    let mut tokens = value.into_iter();
+    match (tokens.next(), tokens.next()) {
+        (Some(TokenTree::Group(group)), None)
+            if group.delimiter() == proc_macro2::Delimiter::Bracket =>
+        {
+            Ok(group.stream())
+        }
+        _ => Err(syn::Error::new(
+            ident.span(),
+            format!("expected the seeds of `{ident}` to be a list, e.g. `seeds = [b\"seed\"]`"),
+        )),
+    }
+}
+
+/// Whether `tokens` access `ident` other than through `ident.key()`, e.g. `ident.data`.
+fn reads_data(tokens: &TokenStream2, ident: &Ident) -> bool {
+    let tokens: Vec<TokenTree> = tokens.clone().into_iter().collect();
+    tokens.iter().enumerate().any(|(index, token)| match token {
+        TokenTree::Group(group) => reads_data(&group.stream(), ident),
+        TokenTree::Ident(found) if found == ident => {
+            match (tokens.get(index + 1), tokens.get(index + 2)) {
+                (Some(TokenTree::Punct(punct)), Some(TokenTree::Ident(member)))
+                    if punct.as_char() == '.' =>
+                {
+                    member != "key"
+                }
+                _ => false,
+            }
+        }
+        _ => false,
+    })
+}
+
+fn kind(ty: &Type) -> Kind {
+    let Type::Path(path) = ty else {
+        return Kind::Other;
+    };
+    let Some(segment) = path.path.segments.last() else {
+        return Kind::Other;
+    };
+    let type_args: Vec<&Type> = match &segment.arguments {
+        PathArguments::AngleBracketed(args) => args
+            .args
+            .iter()
+            .filter_map(|arg| match arg {
+                GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            })
+            .collect(),
+        _ => vec![],
+    };
+    match (segment.ident.to_string().as_str(), type_args.as_slice()) {
+        ("Box", [inner]) => kind(inner),
+        ("Signer", _) => Kind::Signer,
+        ("Account", [inner]) => Kind::Account((*inner).clone()),
+        ("Program", [inner]) => Kind::Program((*inner).clone()),
+        ("Sysvar", [inner]) => Kind::Sysvar((*inner).clone()),
+        _ => Kind::Other,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_constraints() {
+        let constraints = parse_constraints(quote! {
+            init, payer = payer, seeds = [b"bar", &[foo.data]], bump,
+            seeds::program = other.key(), constraint = a == b @ MyError::Invalid
+        })
+        .unwrap();
+        let keys: Vec<&str> = constraints.iter().map(|c| c.key.as_str()).collect();
+        assert_eq!(
+            keys,
+            [
+                "init",
+                "payer",
+                "seeds",
+                "bump",
+                "seeds::program",
+                "constraint"
+            ]
+        );
+        let values: Vec<Option<String>> = constraints
+            .iter()
+            .map(|c| c.value.as_ref().map(|value| value.to_string()))
+            .collect();
+        assert_eq!(values[0], None);
+        assert_eq!(values[1].as_deref(), Some("payer"));
+        assert_eq!(values[2].as_deref(), Some("[b\"bar\" , & [foo . data]]"));
+        assert_eq!(values[4].as_deref(), Some("other . key ()"));
+        assert_eq!(values[5].as_deref(), Some("a == b"));
+        assert!(parse_constraints(quote!(payer payer)).is_err());
+    }
+
+    #[test]
+    fn test_reads_data() {
+        let foo = format_ident!("foo");
+        assert!(reads_data(&quote!([b"bar", &[foo.data]]), &foo));
+        assert!(reads_data(&quote!(foo.to_account_info().key), &foo));
+        assert!(!reads_data(&quote!([b"bar", foo.key().as_ref()]), &foo));
+        assert!(!reads_data(&quote!([b"foo", bar.data]), &foo));
+    }
+
+    #[test]
+    fn test_refers_to() {
+        let foo = format_ident!("foo");
+        assert!(refers_to(&quote!([b"bar", foo.key().as_ref()]), &foo));
+        assert!(refers_to(&quote!(&[foo.data]), &foo));
+        assert!(refers_to(&quote!(foo), &foo));
+        assert!(!refers_to(&quote!([b"bar", bar.foo.as_ref()]), &foo));
+        assert!(!refers_to(&quote!(foo::ID), &foo));
+        assert!(!refers_to(&quote!(crate::foo), &foo));
+    }
+
+    #[test]
+    fn test_instruction_args() {
+        let input: DeriveInput = syn::parse2(quote! {
+            #[instruction(amount: u64, map: HashMap<u8, Vec<u8>>, path: std::string::String)]
+            pub struct Example {}
+        })
+        .unwrap();
+        let args: Vec<String> = instruction_args(&input.attrs)
+            .unwrap()
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect();
+        assert_eq!(args, ["amount", "map", "path"]);
+    }
+
+    fn expand_str(input: TokenStream2) -> Result<String, String> {
+        expand(syn::parse2(input).unwrap())
+            .map(|tokens| tokens.to_string())
+            .map_err(|err| err.to_string())
+    }
+
+    /// The generated meta of `ident`.
+    fn meta(ident: &str, is_signer: bool, is_writable: bool) -> String {
+        format!("pubkey : __key_{ident} , is_signer : {is_signer} , is_writable : {is_writable} ,")
+    }
+
+    #[test]
+    fn test_expand() {
+        let expanded = expand_str(quote! {
+            #[resolve(program_id = my_program::ID)]
+            pub struct Example<'info> {
+                #[account(mut)]
+                pub payer: Signer<'info>,
+                #[account(seeds = [b"foo"], bump)]
+                pub foo: Account<'info, MyAccount>,
+                #[account(init, payer = payer, space = 8, seeds = [b"bar", &[foo.data]], bump)]
+                pub bar: Box<Account<'info, MyAccount>>,
+                #[account(mut, address = FEE_COLLECTOR @ MyError::Invalid)]
+                pub fee_collector: UncheckedAccount<'info>,
+                pub system_program: Program<'info, System>,
+                pub clock: Sysvar<'info, Clock>,
+            }
+        })
+        .unwrap();
+        assert!(expanded.contains("RESOLVER_PUBKEY_PAYER"));
+        assert!(expanded.contains("Account :: < '__info , MyAccount > :: try_from"));
+        assert!(expanded.contains("let bar = __key_bar"));
+        assert!(expanded.contains(
+            "let __key_fee_collector : :: anchor_lang :: prelude :: Pubkey = FEE_COLLECTOR ;"
+        ));
+        assert!(expanded.contains("< System as :: anchor_lang :: Id > :: id ()"));
+        assert!(expanded.contains(
+            "< Clock as :: anchor_lang :: solana_program :: sysvar :: SysvarId > :: id ()"
+        ));
+        assert!(expanded.contains("= my_program :: ID"));
+        for (ident, is_signer, is_writable) in [
+            ("payer", true, true),
+            ("foo", false, false),
+            ("bar", false, true),
+            ("fee_collector", false, true),
+            ("system_program", false, false),
+            ("clock", false, false),
+        ] {
+            assert!(
+                expanded.contains(&meta(ident, is_signer, is_writable)),
+                "{ident}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_expand_signers() {
+        let expanded = expand_str(quote! {
+            pub struct Example<'info> {
+                #[account(init, payer = payer, space = 8)]
+                #[resolve(address = RESOLVER_PUBKEY_KEYPAIR_00)]
+                pub new_account: Account<'info, MyAccount>,
+                #[account(init_if_needed, payer = payer, space = 8)]
+                #[resolve(address = RESOLVER_PUBKEY_KEYPAIR_01)]
+                pub maybe_new_account: Account<'info, MyAccount>,
+                #[account(init, payer = payer, space = 8, seeds = [b"foo"], bump)]
+                pub pda: Account<'info, MyAccount>,
+                #[account(init_if_needed, payer = payer, associated_token::mint = mint, associated_token::authority = payer)]
+                #[resolve(address = TOKEN_ACCOUNT)]
+                pub token_account: Account<'info, TokenAccount>,
+                #[account(signer)]
+                #[resolve(address = AUTHORITY)]
+                pub authority: UncheckedAccount<'info>,
+            }
+        })
+        .unwrap();
+        for (ident, is_signer, is_writable) in [
+            ("new_account", true, true),
+            ("maybe_new_account", true, true),
+            ("pda", false, true),
+            ("token_account", false, true),
+            ("authority", true, false),
+        ] {
+            assert!(
+                expanded.contains(&meta(ident, is_signer, is_writable)),
+                "{ident}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_expand_errors() {
+        let err = expand_str(quote! {
+            pub struct Example<'info> {
+                #[account(init, payer = payer, space = 8)]
+                pub new_account: Account<'info, MyAccount>,
+            }
+        })
+        .unwrap_err();
+        assert!(err.contains("cannot derive the address of `new_account`"));
+
+        let err = expand_str(quote! {
+            pub struct Example<'info> {
+                #[account(seeds = [b"foo"], bump)]
+                pub foo: UncheckedAccount<'info>,
+                #[account(seeds = [foo.data.borrow().as_ref()], bump)]
+                pub bar: UncheckedAccount<'info>,
+            }
+        })
+        .unwrap_err();
+        assert!(err.contains("the data of `foo` is read by a later account"));
+
+        let err = expand_str(quote! {
+            #[instruction(amount: u64)]
+            pub struct Example<'info> {
+                #[account(seeds = [b"foo", &amount.to_le_bytes()], bump)]
+                pub foo: Account<'info, MyAccount>,
+            }
+        })
+        .unwrap_err();
+        assert!(err.contains(
+            "the address of `foo` refers to the instruction argument `amount`, which is not available to the resolver"
+        ));
+
+        let err = expand_str(quote! {
+            pub struct Example<'info> {
+                #[account(seeds = [b"foo", bar.key().as_ref()], bump)]
+                pub foo: Account<'info, MyAccount>,
+                #[account(seeds = [b"bar"], bump)]
+                pub bar: Account<'info, MyAccount>,
+            }
+        })
+        .unwrap_err();
+        assert!(
+            err.contains("the address of `foo` refers to `bar`, which is not declared before it")
+        );
+
+        // a new keypair account can be given a placeholder, and signs
+        let expanded = expand_str(quote! {
+            pub struct Example<'info> {
+                #[account(init, payer = payer, space = 8)]
+                #[resolve(address = RESOLVER_PUBKEY_KEYPAIR_00)]
+                pub new_account: Account<'info, MyAccount>,
+            }
+        })
+        .unwrap();
+        assert!(expanded.contains(&meta("new_account", true, true)));
+    }
+}
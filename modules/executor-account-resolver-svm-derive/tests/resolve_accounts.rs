@@ -0,0 +1,124 @@
+// the `Accounts` derive checks the `anchor-debug` feature of this crate
+#![allow(unexpected_cfgs)]
+
+use anchor_lang::prelude::*;
+use executor_account_resolver_svm::{
+    ResolveAccounts, ResolverError, SerializableAccountMeta, RESOLVER_PUBKEY_KEYPAIR_00,
+    RESOLVER_PUBKEY_PAYER,
+};
+
+declare_id!("8mjNDtRMN7Sjq2ZVjCjKJUUaCfUdfZLoeYREmYs3yKSi");
+
+const OTHER_PROGRAM: Pubkey = Pubkey::new_from_array([7; 32]);
+
+#[account]
+pub struct Counter {
+    pub count: u8,
+}
+
+#[derive(Accounts, ResolveAccounts)]
+pub struct Increment<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(seeds = [b"counter"], bump)]
+    pub counter: Account<'info, Counter>,
+
+    #[account(mut, seeds = [b"next", &[counter.count]], bump)]
+    pub next: Account<'info, Counter>,
+
+    /// CHECK: derived from another program
+    #[account(seeds = [next.key().as_ref()], bump, seeds::program = OTHER_PROGRAM)]
+    pub other: UncheckedAccount<'info>,
+
+    #[account(init, payer = payer, space = 8 + 1)]
+    #[resolve(address = RESOLVER_PUBKEY_KEYPAIR_00)]
+    pub new_counter: Account<'info, Counter>,
+
+    pub system_program: Program<'info, System>,
+
+    pub clock: Sysvar<'info, Clock>,
+}
+
+fn missing(
+    result: std::result::Result<Vec<SerializableAccountMeta>, ResolverError>,
+) -> Vec<Pubkey> {
+    match result {
+        Err(ResolverError::Missing(missing)) => missing.accounts,
+        _ => panic!("expected missing accounts"),
+    }
+}
+
+#[test]
+fn test_resolve_account_metas() {
+    let counter_key = Pubkey::find_program_address(&[b"counter"], &ID).0;
+    let next_key = Pubkey::find_program_address(&[b"next", &[3]], &ID).0;
+    let other_key = Pubkey::find_program_address(&[next_key.as_ref()], &OTHER_PROGRAM).0;
+
+    assert_eq!(
+        missing(Increment::resolve_account_metas(&[])),
+        [counter_key]
+    );
+
+    let mut lamports = 1;
+    let mut data = Counter::DISCRIMINATOR.to_vec();
+    data.push(3);
+    let counter = AccountInfo::new(
+        &counter_key,
+        false,
+        false,
+        &mut lamports,
+        &mut data,
+        &ID,
+        false,
+        0,
+    );
+    let remaining_accounts = [counter];
+    let metas = Increment::resolve_account_metas(&remaining_accounts)
+        .ok()
+        .unwrap();
+    let meta = |pubkey, is_signer, is_writable| (pubkey, is_signer, is_writable);
+    assert_eq!(
+        metas
+            .iter()
+            .map(|m| meta(m.pubkey, m.is_signer, m.is_writable))
+            .collect::<Vec<_>>(),
+        [
+            meta(RESOLVER_PUBKEY_PAYER, true, true),
+            meta(counter_key, false, false),
+            meta(next_key, false, true),
+            meta(other_key, false, false),
+            meta(RESOLVER_PUBKEY_KEYPAIR_00, true, true),
+            meta(System::id(), false, false),
+            meta(anchor_lang::solana_program::sysvar::clock::ID, false, false),
+        ]
+    );
+
+    let instruction = Increment::resolve_instruction(&remaining_accounts, vec![1, 2])
+        .ok()
+        .unwrap();
+    assert_eq!(instruction.program_id, ID);
+    assert_eq!(instruction.accounts.len(), 7);
+    assert_eq!(instruction.data, [1, 2]);
+}
+
+#[test]
+fn test_resolve_account_metas_invalid_account() {
+    let counter_key = Pubkey::find_program_address(&[b"counter"], &ID).0;
+    let mut lamports = 1;
+    let mut data = vec![0; 9];
+    let counter = AccountInfo::new(
+        &counter_key,
+        false,
+        false,
+        &mut lamports,
+        &mut data,
+        &ID,
+        false,
+        0,
+    );
+    assert!(matches!(
+        Increment::resolve_account_metas(&[counter]),
+        Err(ResolverError::Error(_))
+    ));
+}
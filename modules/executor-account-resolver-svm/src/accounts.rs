@@ -0,0 +1,231 @@
+//! Indexed lookup of `remaining_accounts` by pubkey, for resolvers which look up many accounts.
+
+use anchor_lang::prelude::*;
+
+use crate::{InstructionGroups, MissingCollector, Resolver};
+
+/// An index of a resolver's `remaining_accounts`, built once and then looked up by pubkey.
+///
+/// `find_account` scans every account on each lookup, while `ResolverAccounts` sorts the accounts
+/// once and then binary searches them, which is cheaper for resolvers which look up many accounts.
+/// Lookups of absent keys are recorded, so the resolver can request them all at once.
+///
+/// Usage:
+///
+/// ```rust
+/// use anchor_lang::prelude::*;
+/// use executor_account_resolver_svm::{
+///     InstructionGroups, Resolver, ResolverAccounts, RESOLVER_EXECUTE_VAA_V1,
+/// };
+///
+/// #[derive(Accounts)]
+/// pub struct Resolve {}
+///
+/// #[instruction(discriminator = &RESOLVER_EXECUTE_VAA_V1)]
+/// pub fn resolve_execute_vaa_v1(ctx: Context<Resolve>, vaa_body: Vec<u8>) -> Result<Resolver<InstructionGroups>> {
+///     let mut accounts = ResolverAccounts::new(ctx.remaining_accounts);
+///     let mint = accounts.get(&pubkey!("So11111111111111111111111111111111111111112"));
+///     let config = accounts.get(&pubkey!("SysvarC1ock11111111111111111111111111111111"));
+///     let (Some(mint), Some(config)) = (mint, config) else {
+///         return Ok(accounts.missing_resolver().unwrap());
+///     };
+///     Ok(Resolver::Resolved(InstructionGroups(vec![
+///         // build your `InstructionGroup`s here
+///     ])))
+/// }
+/// ```
+pub struct ResolverAccounts<'c, 'info> {
+    accounts: &'c [AccountInfo<'info>],
+    /// Indexes into `accounts`, sorted by pubkey.
+    sorted: Vec<u16>,
+    missing: MissingCollector,
+}
+
+impl<'c, 'info> ResolverAccounts<'c, 'info> {
+    pub fn new(accounts: &'c [AccountInfo<'info>]) -> Self {
+        // a transaction cannot lock more than 256 accounts, so the indexes always fit
+        let mut sorted: Vec<u16> = (0..accounts.len() as u16).collect();
+        sorted.sort_unstable_by(|a, b| {
+            accounts[usize::from(*a)]
+                .key
+                .cmp(accounts[usize::from(*b)].key)
+        });
+        ResolverAccounts {
+            accounts,
+            sorted,
+            missing: MissingCollector::new(),
+        }
+    }
+
+    /// The accounts in their original order.
+    pub fn accounts(&self) -> &'c [AccountInfo<'info>] {
+        self.accounts
+    }
+
+    /// Whether `key` was supplied, without recording it as missing.
+    pub fn contains(&self, key: &Pubkey) -> bool {
+        self.find(key).is_some()
+    }
+
+    /// The account for `key`, recording `key` as missing if it was not supplied.
+    pub fn get(&mut self, key: &Pubkey) -> Option<&'c AccountInfo<'info>> {
+        let account = self.find(key);
+        if account.is_none() {
+            self.missing.add_account(*key);
+        }
+        account
+    }
+
+    /// The keys looked up with `get` which were not supplied, in the order they were first looked up.
+    pub fn missing(&self) -> &[Pubkey] {
+        self.missing.accounts()
+    }
+
+    /// The collector of the keys which were not supplied, e.g. to add lookup tables or accounts needed later.
+    pub fn missing_collector(&mut self) -> &mut MissingCollector {
+        &mut self.missing
+    }
+
+    /// `Resolver::Missing` with everything collected, if anything is missing.
+    pub fn missing_resolver(&self) -> Option<Resolver<InstructionGroups>> {
+        self.missing.clone().into_resolver()
+    }
+
+    fn find(&self, key: &Pubkey) -> Option<&'c AccountInfo<'info>> {
+        let accounts = self.accounts;
+        self.sorted
+            .binary_search_by(|index| accounts[usize::from(*index)].key.cmp(key))
+            .ok()
+            .map(|position| &accounts[usize::from(self.sorted[position])])
+    }
+}
+
+impl<'c: 'info, 'info> ResolverAccounts<'c, 'info> {
+    /// The account for `key` deserialized as `T`, recording `key` as missing if it was not supplied.
+    /// Fails if the account is not owned by `T`'s program or cannot be deserialized.
+    pub fn account<T>(&mut self, key: &Pubkey) -> Result<Option<Account<'info, T>>>
+    where
+        T: AccountSerialize + AccountDeserialize + Owner + Clone,
+    {
+        self.get(key).map(Account::try_from).transpose()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const OWNER: Pubkey = Pubkey::new_from_array([5; 32]);
+
+    #[derive(Clone)]
+    struct Counter(u8);
+
+    impl AccountSerialize for Counter {}
+
+    impl AccountDeserialize for Counter {
+        fn try_deserialize_unchecked(buf: &mut &[u8]) -> Result<Self> {
+            let (count, rest) = buf
+                .split_first()
+                .ok_or(ErrorCode::AccountDidNotDeserialize)?;
+            *buf = rest;
+            Ok(Counter(*count))
+        }
+    }
+
+    impl Owner for Counter {
+        fn owner() -> Pubkey {
+            OWNER
+        }
+    }
+
+    struct Accounts {
+        keys: Vec<Pubkey>,
+        lamports: Vec<u64>,
+        data: Vec<Vec<u8>>,
+    }
+
+    impl Accounts {
+        fn new(count: u8) -> Self {
+            Accounts {
+                keys: (0..count)
+                    .map(|n| Pubkey::new_from_array([n.wrapping_mul(97); 32]))
+                    .collect(),
+                lamports: vec![1; usize::from(count)],
+                data: (0..count).map(|n| vec![n]).collect(),
+            }
+        }
+
+        fn infos(&mut self) -> Vec<AccountInfo<'_>> {
+            self.keys
+                .iter()
+                .zip(self.lamports.iter_mut())
+                .zip(self.data.iter_mut())
+                .map(|((key, lamports), data)| {
+                    AccountInfo::new(key, false, false, lamports, data, &OWNER, false, 0)
+                })
+                .collect()
+        }
+    }
+
+    #[test]
+    fn test_resolver_accounts_get() {
+        let mut accounts = Accounts::new(68);
+        let infos = accounts.infos();
+        let mut resolver_accounts = ResolverAccounts::new(&infos);
+        for info in &infos {
+            assert_eq!(resolver_accounts.get(info.key).unwrap().key, info.key);
+            assert!(resolver_accounts.contains(info.key));
+        }
+        assert!(resolver_accounts.missing().is_empty());
+        assert!(resolver_accounts.missing_resolver().is_none());
+        assert_eq!(resolver_accounts.accounts().len(), 68);
+    }
+
+    #[test]
+    fn test_resolver_accounts_missing() {
+        let mut accounts = Accounts::new(3);
+        let infos = accounts.infos();
+        let mut resolver_accounts = ResolverAccounts::new(&infos);
+        let absent = [Pubkey::new_unique(), Pubkey::new_unique()];
+        assert!(!resolver_accounts.contains(&absent[0]));
+        assert!(resolver_accounts.missing().is_empty());
+        assert!(resolver_accounts.get(&absent[1]).is_none());
+        assert!(resolver_accounts.get(infos[1].key).is_some());
+        assert!(resolver_accounts.get(&absent[0]).is_none());
+        assert!(resolver_accounts.get(&absent[1]).is_none());
+        assert_eq!(resolver_accounts.missing(), [absent[1], absent[0]]);
+        let Some(Resolver::Missing(missing)) = resolver_accounts.missing_resolver() else {
+            panic!("expected missing accounts");
+        };
+        assert_eq!(missing.accounts, [absent[1], absent[0]]);
+
+        // an empty index has no accounts
+        let mut empty = ResolverAccounts::new(&[]);
+        assert!(empty.get(&absent[0]).is_none());
+        assert_eq!(empty.missing(), [absent[0]]);
+    }
+
+    #[test]
+    fn test_resolver_accounts_account() {
+        let mut accounts = Accounts::new(3);
+        accounts.data[2].clear();
+        let infos = accounts.infos();
+        let mut resolver_accounts = ResolverAccounts::new(&infos);
+        let counter = resolver_accounts
+            .account::<Counter>(infos[1].key)
+            .unwrap()
+            .unwrap();
+        assert_eq!(counter.0, 1);
+        assert!(resolver_accounts
+            .account::<Counter>(&Pubkey::new_unique())
+            .unwrap()
+            .is_none());
+        assert_eq!(
+            resolver_accounts
+                .account::<Counter>(infos[2].key)
+                .err()
+                .unwrap(),
+            ErrorCode::AccountDidNotDeserialize.into()
+        );
+    }
+}
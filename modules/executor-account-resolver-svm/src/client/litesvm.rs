@@ -0,0 +1,322 @@
+//! An in-process [`SimulationBackend`] built on [LiteSVM](https://github.com/LiteSVM/litesvm),
+//! enabled by the `litesvm` feature.
+//!
+//! This allows resolvers to be tested with a plain `cargo test` instead of a local validator.
+//!
+//! Usage:
+//!
+//! ```ignore
+//! let mut backend = LiteSvmBackend::new();
+//! backend.add_program_from_file(my_program::ID, "../../target/deploy/my_program.so")?;
+//! backend.set_account(config_key, config_account)?;
+//! backend.airdrop(&payer, 1_000_000_000)?;
+//! let groups = ResolverClient::new(backend, payer).resolve_execute_vaa_v1(&my_program::ID, &vaa_body)?;
+//! ```
+
+use std::{borrow::Cow, fmt, path::Path};
+
+use ::litesvm::{error::LiteSVMError, types::FailedTransactionMetadata, LiteSVM};
+use anchor_lang::{prelude::*, solana_program::instruction::InstructionError};
+use solana_account::Account;
+use solana_address_lookup_table_interface::{
+    self as address_lookup_table,
+    state::{AddressLookupTable, LookupTableMeta, LOOKUP_TABLE_META_SIZE},
+};
+use solana_message::AddressLookupTableAccount;
+
+use super::{SimulationBackend, SimulationRequest, SimulationResult};
+
+#[derive(Debug)]
+pub enum LiteSvmBackendError {
+    /// The simulated transaction failed.
+    Simulation(Box<FailedTransactionMetadata>),
+    /// A requested lookup table does not exist.
+    LookupTableNotFound(Pubkey),
+    /// A requested lookup table could not be deserialized.
+    InvalidLookupTable(Pubkey, InstructionError),
+    /// LiteSVM rejected an account fixture.
+    Svm(LiteSVMError),
+    /// A program could not be read from disk.
+    Io(std::io::Error),
+}
+
+impl fmt::Display for LiteSvmBackendError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LiteSvmBackendError::Simulation(failed) => {
+                write!(f, "simulation failed: {}", failed.err)?;
+                for log in &failed.meta.logs {
+                    write!(f, "\n  {log}")?;
+                }
+                Ok(())
+            }
+            LiteSvmBackendError::LookupTableNotFound(key) => {
+                write!(f, "lookup table {key} not found")
+            }
+            LiteSvmBackendError::InvalidLookupTable(key, err) => {
+                write!(f, "invalid lookup table {key}: {err}")
+            }
+            LiteSvmBackendError::Svm(err) => write!(f, "{err}"),
+            LiteSvmBackendError::Io(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for LiteSvmBackendError {}
+
+/// A [`SimulationBackend`] which simulates against an in-process [`LiteSVM`].
+pub struct LiteSvmBackend {
+    svm: LiteSVM,
+}
+
+impl Default for LiteSvmBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LiteSvmBackend {
+    /// Creates a backend with a default `LiteSVM`.
+    /// Signature verification and blockhash checks are disabled, as resolution transactions are unsigned.
+    pub fn new() -> Self {
+        Self::from_svm(LiteSVM::new())
+    }
+
+    /// Wraps an existing `LiteSVM`, disabling signature verification and blockhash checks.
+    pub fn from_svm(svm: LiteSVM) -> Self {
+        LiteSvmBackend {
+            svm: svm.with_sigverify(false).with_blockhash_check(false),
+        }
+    }
+
+    pub fn svm(&self) -> &LiteSVM {
+        &self.svm
+    }
+
+    pub fn svm_mut(&mut self) -> &mut LiteSVM {
+        &mut self.svm
+    }
+
+    /// Loads a program, e.g. `target/deploy/<program>.so` produced by `anchor build`.
+    pub fn add_program_from_file(
+        &mut self,
+        program_id: Pubkey,
+        path: impl AsRef<Path>,
+    ) -> std::result::Result<(), LiteSvmBackendError> {
+        self.svm
+            .add_program_from_file(program_id, path)
+            .map_err(LiteSvmBackendError::Io)
+    }
+
+    pub fn add_program(&mut self, program_id: Pubkey, program_bytes: &[u8]) {
+        self.svm.add_program(program_id, program_bytes);
+    }
+
+    /// Seeds an account fixture.
+    pub fn set_account(
+        &mut self,
+        pubkey: Pubkey,
+        account: Account,
+    ) -> std::result::Result<(), LiteSvmBackendError> {
+        self.svm
+            .set_account(pubkey, account)
+            .map_err(LiteSvmBackendError::Svm)
+    }
+
+    /// Seeds a rent-exempt account fixture owned by `owner`, e.g. an Anchor account serialized with its discriminator.
+    pub fn set_program_account(
+        &mut self,
+        pubkey: Pubkey,
+        owner: Pubkey,
+        data: Vec<u8>,
+    ) -> std::result::Result<(), LiteSvmBackendError> {
+        let lamports = self.svm.minimum_balance_for_rent_exemption(data.len());
+        self.set_account(
+            pubkey,
+            Account {
+                lamports,
+                data,
+                owner,
+                executable: false,
+                rent_epoch: 0,
+            },
+        )
+    }
+
+    /// Seeds an active, frozen address lookup table fixture containing `addresses`.
+    ///
+    /// Addresses are only usable after the slot in which they were extended,
+    /// so the SVM is warped to slot 1 if it is still at slot 0.
+    pub fn set_address_lookup_table(
+        &mut self,
+        key: Pubkey,
+        addresses: &[Pubkey],
+    ) -> std::result::Result<(), LiteSvmBackendError> {
+        if self.svm.get_sysvar::<Clock>().slot == 0 {
+            self.svm.warp_to_slot(1);
+        }
+        let table = AddressLookupTable {
+            meta: LookupTableMeta::default(),
+            addresses: Cow::Borrowed(addresses),
+        };
+        let mut data = vec![0; LOOKUP_TABLE_META_SIZE];
+        AddressLookupTable::overwrite_meta_data(&mut data, table.meta)
+            .map_err(|err| LiteSvmBackendError::InvalidLookupTable(key, err))?;
+        for address in table.addresses.iter() {
+            data.extend_from_slice(address.as_ref());
+        }
+        let lamports = self.svm.minimum_balance_for_rent_exemption(data.len());
+        self.set_account(
+            key,
+            Account {
+                lamports,
+                data,
+                owner: address_lookup_table::program::ID,
+                executable: false,
+                rent_epoch: 0,
+            },
+        )
+    }
+
+    /// Funds the fee payer used for simulation.
+    pub fn airdrop(
+        &mut self,
+        pubkey: &Pubkey,
+        lamports: u64,
+    ) -> std::result::Result<(), LiteSvmBackendError> {
+        self.svm
+            .airdrop(pubkey, lamports)
+            .map(|_| ())
+            .map_err(|failed| LiteSvmBackendError::Simulation(Box::new(failed)))
+    }
+}
+
+impl SimulationBackend for LiteSvmBackend {
+    type Error = LiteSvmBackendError;
+
+    fn simulate(
+        &mut self,
+        request: &SimulationRequest,
+    ) -> std::result::Result<SimulationResult, Self::Error> {
+        let simulated = self
+            .svm
+            .simulate_transaction(request.transaction.clone())
+            .map_err(|failed| LiteSvmBackendError::Simulation(Box::new(failed)))?;
+        let return_data = &simulated.meta.return_data;
+        let accounts = request
+            .accounts
+            .iter()
+            .map(|pubkey| {
+                // accounts which were not part of the transaction are unchanged
+                match simulated
+                    .post_accounts
+                    .iter()
+                    .find(|(key, _)| key == pubkey)
+                {
+                    Some((_, account)) => Account::from(account.clone()),
+                    None => self.svm.get_account(pubkey).unwrap_or_default(),
+                }
+            })
+            .map(|account| (account.lamports > 0).then_some(account.data))
+            .collect();
+        Ok(SimulationResult {
+            return_data: (return_data.program_id == request.program_id)
+                .then(|| return_data.data.clone()),
+            accounts,
+        })
+    }
+
+    fn address_lookup_tables(
+        &mut self,
+        addresses: &[Pubkey],
+    ) -> std::result::Result<Vec<AddressLookupTableAccount>, Self::Error> {
+        addresses
+            .iter()
+            .map(|key| {
+                let account = self
+                    .svm
+                    .get_account(key)
+                    .ok_or(LiteSvmBackendError::LookupTableNotFound(*key))?;
+                let table = AddressLookupTable::deserialize(&account.data)
+                    .map_err(|err| LiteSvmBackendError::InvalidLookupTable(*key, err))?;
+                Ok(AddressLookupTableAccount {
+                    key: *key,
+                    addresses: table.addresses.to_vec(),
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::client::ResolverClient;
+    use anchor_lang::{solana_program::system_instruction, system_program};
+
+    #[test]
+    fn test_address_lookup_table_fixture() {
+        let mut backend = LiteSvmBackend::new();
+        let key = Pubkey::new_unique();
+        let addresses: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        backend.set_address_lookup_table(key, &addresses).unwrap();
+        let tables = backend.address_lookup_tables(&[key]).unwrap();
+        assert_eq!(tables.len(), 1);
+        assert_eq!(tables[0].key, key);
+        assert_eq!(tables[0].addresses, addresses);
+        assert!(matches!(
+            backend.address_lookup_tables(&[Pubkey::new_unique()]),
+            Err(LiteSvmBackendError::LookupTableNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_simulate_post_accounts() {
+        let payer = Pubkey::new_unique();
+        let recipient = Pubkey::new_unique();
+        let fixture = Pubkey::new_unique();
+        let mut backend = LiteSvmBackend::new();
+        backend.airdrop(&payer, 1_000_000_000).unwrap();
+        backend
+            .set_program_account(fixture, Pubkey::new_unique(), vec![1, 2, 3])
+            .unwrap();
+        let message = solana_message::v0::Message::try_compile(
+            &payer,
+            &[system_instruction::transfer(&payer, &recipient, 1_000_000)],
+            &[],
+            backend.svm().latest_blockhash(),
+        )
+        .unwrap();
+        let request = SimulationRequest {
+            program_id: Pubkey::new_unique(),
+            transaction: solana_transaction::versioned::VersionedTransaction {
+                signatures: vec![Default::default()],
+                message: solana_message::VersionedMessage::V0(message),
+            },
+            accounts: vec![recipient, fixture, Pubkey::new_unique()],
+        };
+        let result = backend.simulate(&request).unwrap();
+        assert_eq!(result.return_data, None);
+        assert_eq!(
+            result.accounts,
+            vec![Some(vec![]), Some(vec![1, 2, 3]), None]
+        );
+        // simulation does not persist state
+        assert_eq!(backend.svm().get_balance(&recipient), None);
+    }
+
+    #[test]
+    fn test_simulate_missing_program() {
+        let payer = Pubkey::new_unique();
+        let mut backend = LiteSvmBackend::new();
+        backend.airdrop(&payer, 1_000_000_000).unwrap();
+        let mut client = ResolverClient::new(backend, payer);
+        // the system program does not implement `RESOLVER_EXECUTE_VAA_V1`
+        assert!(matches!(
+            client.resolve_execute_vaa_v1(&system_program::ID, &[]),
+            Err(crate::client::ResolveError::Backend(
+                LiteSvmBackendError::Simulation(_)
+            ))
+        ));
+    }
+}
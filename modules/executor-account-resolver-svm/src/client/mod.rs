@@ -0,0 +1,915 @@
+//! Off-chain helpers for driving resolution, enabled by the `client` feature.
+//!
+//! [`ResolverClient`] implements the iterative resolution loop described in the README:
+//! simulate `RESOLVER_EXECUTE_VAA_V1`, parse the `Resolver` result, append any `Missing`
+//! accounts and lookup tables, and repeat until the result is `Resolved`.
+//! The simulation itself is delegated to a [`SimulationBackend`].
+
+use std::{error, fmt, io};
+
+#[cfg(feature = "litesvm")]
+pub mod litesvm;
+pub mod packer;
+#[cfg(feature = "rpc")]
+pub mod rpc;
+pub mod substitution;
+
+use anchor_lang::{
+    prelude::*,
+    solana_program::instruction::{AccountMeta, Instruction},
+};
+use solana_compute_budget_interface::ComputeBudgetInstruction;
+use solana_hash::Hash;
+use solana_message::{v0, AddressLookupTableAccount, CompileError, VersionedMessage};
+use solana_signature::Signature;
+use solana_transaction::versioned::VersionedTransaction;
+
+use crate::{
+    decode::result_account_body, decode_result_account_with_limits, decode_return_data_with_limits,
+    request_result_account_address, result_account_address, result_offset_marker, DecodeError,
+    DecodeLimits, InstructionGroups, InstructionGroupsV2, Resolver, VaaBody,
+    RESOLVER_EXECUTE_VAA_V1, RESOLVER_PUBKEY_PAYER, RESOLVER_RESULT_ACCOUNT,
+};
+
+/// The default number of simulations attempted before giving up on a resolver.
+pub const DEFAULT_MAX_ITERATIONS: usize = 16;
+/// The compute unit limit requested for each simulation.
+pub const DEFAULT_SIMULATION_COMPUTE_UNIT_LIMIT: u32 = 1_000_000;
+
+/// A transaction to be simulated on behalf of a resolver.
+#[derive(Debug, Clone)]
+pub struct SimulationRequest {
+    /// The resolver program. Only return data set by this program is considered.
+    pub program_id: Pubkey,
+    /// An unsigned transaction calling `RESOLVER_EXECUTE_VAA_V1`.
+    /// Backends are expected to skip signature verification and replace the blockhash.
+    pub transaction: VersionedTransaction,
+    /// Accounts whose post-simulation state must be included in the result.
+    pub accounts: Vec<Pubkey>,
+}
+
+/// The outcome of a successful simulation.
+#[derive(Debug, Clone, Default)]
+pub struct SimulationResult {
+    /// The return data set by `SimulationRequest::program_id`, if any.
+    pub return_data: Option<Vec<u8>>,
+    /// The post-simulation data of each of `SimulationRequest::accounts`, in the same order.
+    /// `None` if the account does not exist.
+    pub accounts: Vec<Option<Vec<u8>>>,
+}
+
+/// The environment in which resolution transactions are simulated, e.g. an RPC node or an in-process SVM.
+pub trait SimulationBackend {
+    type Error;
+
+    /// Simulates the transaction, returning the resolver's return data and the requested account states.
+    fn simulate(
+        &mut self,
+        request: &SimulationRequest,
+    ) -> std::result::Result<SimulationResult, Self::Error>;
+
+    /// Fetches the lookup tables requested by a resolver via `MissingAccounts::address_lookup_tables`.
+    fn address_lookup_tables(
+        &mut self,
+        addresses: &[Pubkey],
+    ) -> std::result::Result<Vec<AddressLookupTableAccount>, Self::Error>;
+}
+
+#[derive(Debug)]
+pub enum ResolveError<E> {
+    /// The simulation backend failed.
+    Backend(E),
+    /// The resolution transaction could not be compiled, e.g. a lookup table index overflowed.
+    Compile(CompileError),
+    /// The resolver did not set any return data.
+    MissingReturnData,
+    /// The return data could not be decoded as a `Resolver<InstructionGroups>`.
+    InvalidReturnData(io::Error),
+    /// The resolver returned `Resolver::Account` or `Resolver::AccountAt` but the result account does not exist.
+    MissingResultAccount,
+    /// The result account could not be decoded as a `Resolver<InstructionGroups>`.
+    InvalidResultAccount(DecodeError),
+    /// The resolver did not return `Resolver::Resolved` or `Resolver::ResolvedV2` within the iteration limit.
+    MaxIterationsExceeded(usize),
+    /// The resolver returned `Resolver::Unresolvable`, so the request will never resolve and should not be retried.
+    Unresolvable { code: u32, reason: String },
+}
+
+impl<E: fmt::Display> fmt::Display for ResolveError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResolveError::Backend(err) => write!(f, "simulation backend error: {err}"),
+            ResolveError::Compile(err) => {
+                write!(f, "failed to compile resolution transaction: {err}")
+            }
+            ResolveError::MissingReturnData => write!(f, "resolver did not set return data"),
+            ResolveError::InvalidReturnData(err) => {
+                write!(f, "invalid resolver return data: {err}")
+            }
+            ResolveError::MissingResultAccount => {
+                write!(f, "resolver result account does not exist")
+            }
+            ResolveError::InvalidResultAccount(err) => {
+                write!(f, "invalid resolver result account: {err}")
+            }
+            ResolveError::MaxIterationsExceeded(iterations) => {
+                write!(f, "resolver did not resolve within {iterations} iterations")
+            }
+            ResolveError::Unresolvable { code, reason } => {
+                write!(f, "request is unresolvable (code {code}): {reason}")
+            }
+        }
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> error::Error for ResolveError<E> {}
+
+/// Drives the iterative resolution of a resolver program against a [`SimulationBackend`].
+///
+/// Usage:
+///
+/// ```ignore
+/// let mut client = ResolverClient::new(backend, relayer.pubkey());
+/// let groups = client.resolve_execute_vaa_v1(&program_id, &vaa_body)?;
+/// ```
+pub struct ResolverClient<B> {
+    backend: B,
+    payer: Pubkey,
+    max_iterations: usize,
+    compute_unit_limit: u32,
+    decode_limits: DecodeLimits,
+}
+
+impl<B: SimulationBackend> ResolverClient<B> {
+    /// Creates a client which simulates with `payer` as the fee payer.
+    /// `payer` is also substituted for `RESOLVER_PUBKEY_PAYER` in requested accounts.
+    pub fn new(backend: B, payer: Pubkey) -> Self {
+        ResolverClient {
+            backend,
+            payer,
+            max_iterations: DEFAULT_MAX_ITERATIONS,
+            compute_unit_limit: DEFAULT_SIMULATION_COMPUTE_UNIT_LIMIT,
+            decode_limits: DecodeLimits::default(),
+        }
+    }
+
+    pub fn with_max_iterations(mut self, max_iterations: usize) -> Self {
+        self.max_iterations = max_iterations;
+        self
+    }
+
+    pub fn with_compute_unit_limit(mut self, compute_unit_limit: u32) -> Self {
+        self.compute_unit_limit = compute_unit_limit;
+        self
+    }
+
+    /// Sets the limits on the lengths in results decoded from the resolver's return data and result accounts.
+    pub fn with_decode_limits(mut self, decode_limits: DecodeLimits) -> Self {
+        self.decode_limits = decode_limits;
+        self
+    }
+
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    pub fn backend_mut(&mut self) -> &mut B {
+        &mut self.backend
+    }
+
+    pub fn into_backend(self) -> B {
+        self.backend
+    }
+
+    /// Repeatedly simulates `RESOLVER_EXECUTE_VAA_V1` on `program_id` until it returns `Resolver::Resolved`
+    /// or `Resolver::ResolvedV2`. `Resolved` groups are returned without compute budget hints.
+    /// `Resolver::Unresolvable` stops resolution with `ResolveError::Unresolvable`, which is not worth retrying.
+    ///
+    /// The chunks of a `Resolver::Partial` result are collected from the result account after each
+    /// simulation and decoded together once the final chunk is written. Each chunk takes an iteration.
+    pub fn resolve_execute_vaa_v1(
+        &mut self,
+        program_id: &Pubkey,
+        vaa_body: &[u8],
+    ) -> std::result::Result<InstructionGroupsV2, ResolveError<B::Error>> {
+        let result_account = result_account_address(program_id);
+        // the result accounts whose post-simulation state is requested, starting with the canonical result account
+        // and the per-request result account for the VAA digest
+        let mut result_accounts = vec![result_account];
+        if let Ok(vaa) = VaaBody::parse(vaa_body) {
+            result_accounts.push(request_result_account_address(program_id, &vaa.digest()).0);
+        }
+        let mut remaining_accounts: Vec<AccountMeta> = vec![];
+        let mut lookup_tables: Vec<AddressLookupTableAccount> = vec![];
+        // the chunks of a paginated result collected so far
+        let mut partial: Vec<u8> = vec![];
+        for _ in 0..self.max_iterations {
+            let instruction =
+                resolve_execute_vaa_v1_instruction(program_id, vaa_body, &remaining_accounts);
+            let request = SimulationRequest {
+                program_id: *program_id,
+                transaction: self.simulation_transaction(instruction, &lookup_tables)?,
+                accounts: result_accounts.clone(),
+            };
+            let result = self
+                .backend
+                .simulate(&request)
+                .map_err(ResolveError::Backend)?;
+            let return_data = result.return_data.ok_or(ResolveError::MissingReturnData)?;
+            let resolver = match decode_return_data_with_limits(&return_data, &self.decode_limits)
+                .map_err(|err| {
+                ResolveError::InvalidReturnData(invalid_data(err))
+            })? {
+                returned @ (Resolver::Account() | Resolver::AccountAt(_)) => {
+                    let address = match returned {
+                        Resolver::AccountAt(address) => address,
+                        _ => result_account,
+                    };
+                    if address != result_account && !partial.is_empty() {
+                        // the chunks were written to the canonical result account, so the final one must be too
+                        let message = format!(
+                            "paginated result completed in {address} rather than the result account"
+                        );
+                        return Err(ResolveError::InvalidReturnData(invalid_data(message)));
+                    }
+                    let Some(index) = result_accounts.iter().position(|key| *key == address) else {
+                        // simulate again with the account's state requested and the account writable
+                        result_accounts.push(address);
+                        for meta in remaining_accounts
+                            .iter_mut()
+                            .filter(|meta| meta.pubkey == address)
+                        {
+                            meta.is_writable = true;
+                        }
+                        continue;
+                    };
+                    let data = result
+                        .accounts
+                        .into_iter()
+                        .nth(index)
+                        .flatten()
+                        .ok_or(ResolveError::MissingResultAccount)?;
+                    let data = if partial.is_empty() {
+                        data
+                    } else {
+                        partial.extend_from_slice(
+                            result_account_body(&data)
+                                .map_err(ResolveError::InvalidResultAccount)?,
+                        );
+                        [RESOLVER_RESULT_ACCOUNT.as_slice(), &partial].concat()
+                    };
+                    decode_result_account_with_limits(&data, &self.decode_limits)
+                        .map_err(ResolveError::InvalidResultAccount)?
+                }
+                Resolver::Partial { next_offset } => {
+                    let offset = partial.len();
+                    let len = (next_offset as usize).saturating_sub(offset);
+                    if len == 0 {
+                        let message = format!(
+                            "partial result offset {next_offset} does not advance past {offset}"
+                        );
+                        return Err(ResolveError::InvalidReturnData(invalid_data(message)));
+                    }
+                    let data = result
+                        .accounts
+                        .into_iter()
+                        .next()
+                        .flatten()
+                        .ok_or(ResolveError::MissingResultAccount)?;
+                    let chunk = result_account_body(&data)
+                        .map_err(ResolveError::InvalidResultAccount)?
+                        .get(..len)
+                        .ok_or(ResolveError::InvalidResultAccount(DecodeError::Truncated {
+                            len: data.len(),
+                        }))?;
+                    partial.extend_from_slice(chunk);
+                    // request the next chunk via the marker in the last remaining account
+                    let marker =
+                        AccountMeta::new_readonly(result_offset_marker(next_offset), false);
+                    if offset == 0 {
+                        remaining_accounts.push(marker);
+                    } else {
+                        *remaining_accounts.last_mut().unwrap() = marker;
+                    }
+                    continue;
+                }
+                resolver => resolver,
+            };
+            match resolver {
+                Resolver::Resolved(groups) => return Ok(groups.into()),
+                Resolver::ResolvedV2(groups) => return Ok(groups),
+                Resolver::Unresolvable { code, reason } => {
+                    return Err(ResolveError::Unresolvable { code, reason })
+                }
+                Resolver::Missing(missing) => {
+                    // a resolver which needs more accounts restarts its paginated result
+                    if !partial.is_empty() {
+                        partial.clear();
+                        remaining_accounts.pop();
+                    }
+                    remaining_accounts.extend(missing.accounts.into_iter().map(|pubkey| {
+                        if pubkey == RESOLVER_PUBKEY_PAYER {
+                            AccountMeta::new_readonly(self.payer, false)
+                        } else if result_accounts.contains(&pubkey) {
+                            AccountMeta::new(pubkey, false)
+                        } else {
+                            AccountMeta::new_readonly(pubkey, false)
+                        }
+                    }));
+                    // only fetch lookup tables which were not requested by an earlier pass
+                    let mut new_lookup_tables = missing.address_lookup_tables;
+                    new_lookup_tables
+                        .retain(|key| lookup_tables.iter().all(|table| table.key != *key));
+                    if !new_lookup_tables.is_empty() {
+                        lookup_tables.extend(
+                            self.backend
+                                .address_lookup_tables(&new_lookup_tables)
+                                .map_err(ResolveError::Backend)?,
+                        );
+                    }
+                }
+                Resolver::Account() | Resolver::AccountAt(_) | Resolver::Partial { .. } => {
+                    unreachable!("handled above")
+                }
+            }
+        }
+        Err(ResolveError::MaxIterationsExceeded(self.max_iterations))
+    }
+
+    fn simulation_transaction(
+        &self,
+        instruction: Instruction,
+        lookup_tables: &[AddressLookupTableAccount],
+    ) -> std::result::Result<VersionedTransaction, ResolveError<B::Error>> {
+        let message = v0::Message::try_compile(
+            &self.payer,
+            &[
+                instruction,
+                ComputeBudgetInstruction::set_compute_unit_limit(self.compute_unit_limit),
+            ],
+            lookup_tables,
+            Hash::default(),
+        )
+        .map_err(ResolveError::Compile)?;
+        Ok(VersionedTransaction {
+            signatures: vec![Signature::default(); message.header.num_required_signatures.into()],
+            message: VersionedMessage::V0(message),
+        })
+    }
+}
+
+/// Builds the `RESOLVER_EXECUTE_VAA_V1` instruction with the given `remaining_accounts`.
+pub fn resolve_execute_vaa_v1_instruction(
+    program_id: &Pubkey,
+    vaa_body: &[u8],
+    remaining_accounts: &[AccountMeta],
+) -> Instruction {
+    let mut data = Vec::with_capacity(RESOLVER_EXECUTE_VAA_V1.len() + 4 + vaa_body.len());
+    data.extend_from_slice(&RESOLVER_EXECUTE_VAA_V1);
+    data.extend_from_slice(&(vaa_body.len() as u32).to_le_bytes());
+    data.extend_from_slice(vaa_body);
+    Instruction {
+        program_id: *program_id,
+        accounts: remaining_accounts.to_vec(),
+        data,
+    }
+}
+
+/// Decodes a resolver's return data with the default `DecodeLimits`.
+///
+/// The runtime strips trailing zeros from return data, e.g. `Resolver::Resolved(InstructionGroups(vec![]))`
+/// is returned as an empty buffer, so the data is treated as if it were followed by zeros.
+/// A `DecodeError` is returned as the inner error of an `io::ErrorKind::InvalidData` error.
+pub fn decode_return_data(data: &[u8]) -> io::Result<Resolver<InstructionGroups>> {
+    decode_return_data_with_limits(data, &DecodeLimits::default()).map_err(invalid_data)
+}
+
+fn invalid_data<E: Into<Box<dyn error::Error + Send + Sync>>>(err: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{InstructionGroup, InstructionGroupV2, MissingAccounts};
+
+    const PROGRAM_ID: Pubkey = Pubkey::new_from_array([7; 32]);
+    const PAYER: Pubkey = Pubkey::new_from_array([8; 32]);
+
+    /// Emulates a resolver which requests one account per simulation and then
+    /// writes its result to the result account.
+    struct IterativeBackend {
+        required: Vec<Pubkey>,
+        lookup_table: Pubkey,
+        simulations: usize,
+        lookup_table_fetches: usize,
+    }
+
+    impl IterativeBackend {
+        fn resolved() -> Resolver<InstructionGroups> {
+            Resolver::Resolved(InstructionGroups(vec![InstructionGroup {
+                instructions: vec![],
+                address_lookup_tables: vec![Pubkey::new_from_array([9; 32])],
+            }]))
+        }
+    }
+
+    impl SimulationBackend for IterativeBackend {
+        type Error = String;
+
+        fn simulate(
+            &mut self,
+            request: &SimulationRequest,
+        ) -> std::result::Result<SimulationResult, Self::Error> {
+            self.simulations += 1;
+            let VersionedMessage::V0(message) = &request.transaction.message else {
+                return Err("expected a v0 message".to_string());
+            };
+            let instruction = &message.instructions[0];
+            assert_eq!(
+                message.account_keys[usize::from(instruction.program_id_index)],
+                request.program_id
+            );
+            assert_eq!(instruction.data[..8], RESOLVER_EXECUTE_VAA_V1);
+            let supplied = instruction.accounts.len();
+            let mut return_data = vec![];
+            if supplied < self.required.len() {
+                Resolver::<InstructionGroups>::Missing(MissingAccounts {
+                    accounts: vec![self.required[supplied]],
+                    // requested again by every later pass
+                    address_lookup_tables: if supplied >= 1 {
+                        vec![self.lookup_table]
+                    } else {
+                        vec![]
+                    },
+                })
+                .serialize(&mut return_data)
+                .unwrap();
+                return Ok(SimulationResult {
+                    return_data: Some(return_data),
+                    accounts: vec![None],
+                });
+            }
+            let mut account = RESOLVER_RESULT_ACCOUNT.to_vec();
+            Self::resolved().serialize(&mut account).unwrap();
+            // realloc leaves trailing zeros, and Anchor's `exit` leaves the tail of a longer previous result
+            account.extend_from_slice(&[0; 16]);
+            account.extend_from_slice(&[0xff; 16]);
+            Resolver::<InstructionGroups>::Account()
+                .serialize(&mut return_data)
+                .unwrap();
+            Ok(SimulationResult {
+                return_data: Some(return_data),
+                accounts: vec![Some(account)],
+            })
+        }
+
+        fn address_lookup_tables(
+            &mut self,
+            addresses: &[Pubkey],
+        ) -> std::result::Result<Vec<AddressLookupTableAccount>, Self::Error> {
+            self.lookup_table_fetches += 1;
+            Ok(addresses
+                .iter()
+                .map(|key| AddressLookupTableAccount {
+                    key: *key,
+                    addresses: vec![Pubkey::new_unique()],
+                })
+                .collect())
+        }
+    }
+
+    #[test]
+    fn test_resolve_execute_vaa_v1_instruction_data() {
+        let ix = resolve_execute_vaa_v1_instruction(&PROGRAM_ID, &[1, 2, 3], &[]);
+        assert_eq!(ix.program_id, PROGRAM_ID);
+        assert_eq!(
+            ix.data,
+            [148, 184, 169, 222, 207, 8, 154, 127, 3, 0, 0, 0, 1, 2, 3]
+        );
+    }
+
+    #[test]
+    fn test_decode_return_data_trailing_zeros_stripped() {
+        let resolver = decode_return_data(&[]).unwrap();
+        assert!(
+            matches!(resolver, Resolver::Resolved(InstructionGroups(groups)) if groups.is_empty())
+        );
+        let resolver = decode_return_data(&[2]).unwrap();
+        assert!(matches!(resolver, Resolver::Account()));
+    }
+
+    #[test]
+    fn test_resolve_iteratively() {
+        let result_account = result_account_address(&PROGRAM_ID);
+        let backend = IterativeBackend {
+            required: vec![RESOLVER_PUBKEY_PAYER, result_account, Pubkey::new_unique()],
+            lookup_table: Pubkey::new_unique(),
+            simulations: 0,
+            lookup_table_fetches: 0,
+        };
+        let mut client = ResolverClient::new(backend, PAYER);
+        let groups = client
+            .resolve_execute_vaa_v1(&PROGRAM_ID, b"vaa body")
+            .unwrap();
+        assert_eq!(client.backend().simulations, 4);
+        assert_eq!(client.backend().lookup_table_fetches, 1);
+        assert_eq!(groups.0.len(), 1);
+        assert_eq!(
+            groups.0[0].address_lookup_tables,
+            vec![Pubkey::new_from_array([9; 32])]
+        );
+        assert_eq!(groups.0[0].compute_unit_limit, None);
+    }
+
+    #[test]
+    fn test_resolve_resolved_v2() {
+        struct Fixed(Vec<u8>);
+        impl SimulationBackend for Fixed {
+            type Error = ();
+            fn simulate(
+                &mut self,
+                _request: &SimulationRequest,
+            ) -> std::result::Result<SimulationResult, ()> {
+                Ok(SimulationResult {
+                    return_data: Some(self.0.clone()),
+                    accounts: vec![None],
+                })
+            }
+            fn address_lookup_tables(
+                &mut self,
+                _addresses: &[Pubkey],
+            ) -> std::result::Result<Vec<AddressLookupTableAccount>, ()> {
+                unreachable!()
+            }
+        }
+        let mut return_data = vec![];
+        Resolver::<InstructionGroups>::ResolvedV2(InstructionGroupsV2(vec![InstructionGroupV2 {
+            instructions: vec![],
+            address_lookup_tables: vec![],
+            compute_unit_limit: Some(250_000),
+            heap_frame_size: Some(256 * 1024),
+        }]))
+        .serialize(&mut return_data)
+        .unwrap();
+        let mut client = ResolverClient::new(Fixed(return_data.clone()), PAYER);
+        let groups = client.resolve_execute_vaa_v1(&PROGRAM_ID, &[]).unwrap();
+        assert_eq!(groups.0.len(), 1);
+        assert_eq!(groups.0[0].compute_unit_limit, Some(250_000));
+        assert_eq!(groups.0[0].heap_frame_size, Some(256 * 1024));
+
+        // unresolvable requests are reported distinctly, after a single simulation
+        let mut unresolvable = vec![];
+        crate::unresolvable(crate::UNRESOLVABLE_PAUSED, "paused")
+            .serialize(&mut unresolvable)
+            .unwrap();
+        let mut client = ResolverClient::new(Fixed(unresolvable), PAYER).with_max_iterations(1);
+        let err = client.resolve_execute_vaa_v1(&PROGRAM_ID, &[]).unwrap_err();
+        assert!(matches!(
+            &err,
+            ResolveError::Unresolvable { code: 3, reason } if reason == "paused"
+        ));
+
+        // results exceeding the decode limits are rejected
+        let mut client =
+            ResolverClient::new(Fixed(return_data), PAYER).with_decode_limits(DecodeLimits {
+                max_groups: 0,
+                ..DecodeLimits::default()
+            });
+        let Err(ResolveError::InvalidReturnData(err)) =
+            client.resolve_execute_vaa_v1(&PROGRAM_ID, &[])
+        else {
+            panic!("expected invalid return data");
+        };
+        assert!(matches!(
+            err.into_inner().unwrap().downcast_ref::<DecodeError>(),
+            Some(DecodeError::LimitExceeded { len: 1, max: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn test_resolve_remaining_accounts() {
+        let result_account = result_account_address(&PROGRAM_ID);
+        struct Recorder(Vec<VersionedTransaction>);
+        impl SimulationBackend for Recorder {
+            type Error = ();
+            fn simulate(
+                &mut self,
+                request: &SimulationRequest,
+            ) -> std::result::Result<SimulationResult, ()> {
+                self.0.push(request.transaction.clone());
+                let mut return_data = vec![];
+                let resolver: Resolver<InstructionGroups> = if self.0.len() == 1 {
+                    Resolver::Missing(MissingAccounts {
+                        accounts: vec![
+                            RESOLVER_PUBKEY_PAYER,
+                            result_account_address(&request.program_id),
+                            Pubkey::new_from_array([3; 32]),
+                        ],
+                        address_lookup_tables: vec![],
+                    })
+                } else {
+                    Resolver::Resolved(InstructionGroups(vec![]))
+                };
+                resolver.serialize(&mut return_data).unwrap();
+                // emulate the runtime stripping trailing zeros
+                while return_data.last() == Some(&0) {
+                    return_data.pop();
+                }
+                Ok(SimulationResult {
+                    return_data: Some(return_data),
+                    accounts: vec![None],
+                })
+            }
+            fn address_lookup_tables(
+                &mut self,
+                _addresses: &[Pubkey],
+            ) -> std::result::Result<Vec<AddressLookupTableAccount>, ()> {
+                unreachable!()
+            }
+        }
+        let mut client = ResolverClient::new(Recorder(vec![]), PAYER);
+        let groups = client.resolve_execute_vaa_v1(&PROGRAM_ID, &[]).unwrap();
+        assert!(groups.0.is_empty());
+        let transactions = client.into_backend().0;
+        assert_eq!(transactions.len(), 2);
+        let message = &transactions[1].message;
+        let keys = message.static_account_keys();
+        assert_eq!(transactions[1].signatures.len(), 1);
+        assert_eq!(keys[0], PAYER);
+        assert!(message.is_maybe_writable(0, None));
+        let index_of = |key: &Pubkey| keys.iter().position(|k| k == key).unwrap();
+        assert!(message.is_maybe_writable(index_of(&result_account), None));
+        assert!(!message.is_maybe_writable(index_of(&Pubkey::new_from_array([3; 32])), None));
+        assert_eq!(message.instructions()[0].accounts.len(), 3);
+    }
+
+    #[test]
+    fn test_resolve_paginated() {
+        /// Emulates a resolver which writes its result in `CHUNK` byte chunks, requiring an account first.
+        struct Paginated {
+            result: Vec<u8>,
+            required: Pubkey,
+            simulations: usize,
+        }
+        const CHUNK: usize = 40;
+        impl SimulationBackend for Paginated {
+            type Error = ();
+            fn simulate(
+                &mut self,
+                request: &SimulationRequest,
+            ) -> std::result::Result<SimulationResult, ()> {
+                self.simulations += 1;
+                let message = &request.transaction.message;
+                let keys = message.static_account_keys();
+                let accounts: Vec<Pubkey> = message.instructions()[0]
+                    .accounts
+                    .iter()
+                    .map(|index| keys[usize::from(*index)])
+                    .collect();
+                let mut return_data = vec![];
+                if accounts.first() != Some(&self.required) {
+                    // the first pass asks for the account after writing a chunk
+                    let resolver: Resolver<InstructionGroups> = if self.simulations == 1 {
+                        Resolver::Partial { next_offset: 1 }
+                    } else {
+                        Resolver::Missing(MissingAccounts {
+                            accounts: vec![self.required],
+                            address_lookup_tables: vec![],
+                        })
+                    };
+                    resolver.serialize(&mut return_data).unwrap();
+                    let mut account = RESOLVER_RESULT_ACCOUNT.to_vec();
+                    account.extend_from_slice(&self.result[..1]);
+                    return Ok(SimulationResult {
+                        return_data: Some(return_data),
+                        accounts: vec![Some(account)],
+                    });
+                }
+                let offset = (0..self.result.len())
+                    .step_by(CHUNK)
+                    .find(|offset| accounts.last() == Some(&result_offset_marker(*offset as u32)))
+                    .unwrap_or(0);
+                assert_eq!(accounts.len(), if offset == 0 { 1 } else { 2 });
+                let end = usize::min(offset + CHUNK, self.result.len());
+                let mut account = RESOLVER_RESULT_ACCOUNT.to_vec();
+                account.extend_from_slice(&self.result[offset..end]);
+                // stale data after each chunk but the last, which is zeroed by the writer
+                let stale = if end == self.result.len() { 0 } else { 0xff };
+                account.extend_from_slice(&[stale; CHUNK]);
+                let resolver: Resolver<InstructionGroups> = if end == self.result.len() {
+                    Resolver::Account()
+                } else {
+                    Resolver::Partial {
+                        next_offset: end as u32,
+                    }
+                };
+                resolver.serialize(&mut return_data).unwrap();
+                Ok(SimulationResult {
+                    return_data: Some(return_data),
+                    accounts: vec![Some(account)],
+                })
+            }
+            fn address_lookup_tables(
+                &mut self,
+                _addresses: &[Pubkey],
+            ) -> std::result::Result<Vec<AddressLookupTableAccount>, ()> {
+                unreachable!()
+            }
+        }
+        let tables: Vec<Pubkey> = (0..10).map(|n| Pubkey::new_from_array([n; 32])).collect();
+        let mut result = vec![];
+        Resolver::Resolved(InstructionGroups(vec![InstructionGroup {
+            instructions: vec![],
+            address_lookup_tables: tables.clone(),
+        }]))
+        .serialize(&mut result)
+        .unwrap();
+        let backend = Paginated {
+            result,
+            required: Pubkey::new_unique(),
+            simulations: 0,
+        };
+        let mut client = ResolverClient::new(backend, PAYER);
+        let groups = client.resolve_execute_vaa_v1(&PROGRAM_ID, &[]).unwrap();
+        assert_eq!(groups.0.len(), 1);
+        assert_eq!(groups.0[0].address_lookup_tables, tables);
+        // one restarted chunk, the missing account and ceil(333 / 40) chunks
+        assert_eq!(client.backend().simulations, 11);
+    }
+
+    #[test]
+    fn test_resolve_account_at() {
+        /// Writes the result to `address` and records the requested accounts of each simulation.
+        struct AccountAt {
+            address: Pubkey,
+            requests: Vec<Vec<Pubkey>>,
+        }
+        impl SimulationBackend for AccountAt {
+            type Error = ();
+            fn simulate(
+                &mut self,
+                request: &SimulationRequest,
+            ) -> std::result::Result<SimulationResult, ()> {
+                self.requests.push(request.accounts.clone());
+                let mut account = RESOLVER_RESULT_ACCOUNT.to_vec();
+                IterativeBackend::resolved()
+                    .serialize(&mut account)
+                    .unwrap();
+                let mut return_data = vec![];
+                Resolver::<InstructionGroups>::AccountAt(self.address)
+                    .serialize(&mut return_data)
+                    .unwrap();
+                Ok(SimulationResult {
+                    return_data: Some(return_data),
+                    accounts: request
+                        .accounts
+                        .iter()
+                        .map(|key| (*key == self.address).then(|| account.clone()))
+                        .collect(),
+                })
+            }
+            fn address_lookup_tables(
+                &mut self,
+                _addresses: &[Pubkey],
+            ) -> std::result::Result<Vec<AddressLookupTableAccount>, ()> {
+                unreachable!()
+            }
+        }
+        let vaa_body = [0; VaaBody::HEADER_LEN];
+        let digest = VaaBody::parse(&vaa_body).unwrap().digest();
+        let (request_account, _) = request_result_account_address(&PROGRAM_ID, &digest);
+
+        // the per-request result account is requested up front
+        let backend = AccountAt {
+            address: request_account,
+            requests: vec![],
+        };
+        let mut client = ResolverClient::new(backend, PAYER);
+        let groups = client
+            .resolve_execute_vaa_v1(&PROGRAM_ID, &vaa_body)
+            .unwrap();
+        assert_eq!(groups.0.len(), 1);
+        assert_eq!(
+            client.backend().requests,
+            vec![vec![result_account_address(&PROGRAM_ID), request_account]]
+        );
+
+        // any other account requires another simulation
+        let other = Pubkey::new_unique();
+        let backend = AccountAt {
+            address: other,
+            requests: vec![],
+        };
+        let mut client = ResolverClient::new(backend, PAYER);
+        let groups = client
+            .resolve_execute_vaa_v1(&PROGRAM_ID, &vaa_body)
+            .unwrap();
+        assert_eq!(groups.0.len(), 1);
+        assert_eq!(client.backend().requests.len(), 2);
+        assert_eq!(client.backend().requests[1][2], other);
+    }
+
+    #[test]
+    fn test_resolve_partial_account_at() {
+        /// Writes two chunks to the canonical result account and then completes the result in another account.
+        struct Switching {
+            address: Pubkey,
+            simulations: u32,
+        }
+        impl SimulationBackend for Switching {
+            type Error = ();
+            fn simulate(
+                &mut self,
+                request: &SimulationRequest,
+            ) -> std::result::Result<SimulationResult, ()> {
+                self.simulations += 1;
+                let mut return_data = vec![];
+                let resolver: Resolver<InstructionGroups> = if self.simulations < 3 {
+                    Resolver::Partial {
+                        next_offset: self.simulations,
+                    }
+                } else {
+                    Resolver::AccountAt(self.address)
+                };
+                resolver.serialize(&mut return_data).unwrap();
+                let mut account = RESOLVER_RESULT_ACCOUNT.to_vec();
+                IterativeBackend::resolved()
+                    .serialize(&mut account)
+                    .unwrap();
+                Ok(SimulationResult {
+                    return_data: Some(return_data),
+                    accounts: request
+                        .accounts
+                        .iter()
+                        .map(|_| Some(account.clone()))
+                        .collect(),
+                })
+            }
+            fn address_lookup_tables(
+                &mut self,
+                _addresses: &[Pubkey],
+            ) -> std::result::Result<Vec<AddressLookupTableAccount>, ()> {
+                unreachable!()
+            }
+        }
+        let backend = Switching {
+            address: Pubkey::new_unique(),
+            simulations: 0,
+        };
+        let mut client = ResolverClient::new(backend, PAYER);
+        assert!(matches!(
+            client.resolve_execute_vaa_v1(&PROGRAM_ID, &[]),
+            Err(ResolveError::InvalidReturnData(_))
+        ));
+        assert_eq!(client.backend().simulations, 3);
+    }
+
+    #[test]
+    fn test_resolve_partial_must_advance() {
+        struct Stuck;
+        impl SimulationBackend for Stuck {
+            type Error = ();
+            fn simulate(
+                &mut self,
+                _request: &SimulationRequest,
+            ) -> std::result::Result<SimulationResult, ()> {
+                let mut return_data = vec![];
+                Resolver::<InstructionGroups>::Partial { next_offset: 0 }
+                    .serialize(&mut return_data)
+                    .unwrap();
+                Ok(SimulationResult {
+                    return_data: Some(return_data),
+                    accounts: vec![Some(RESOLVER_RESULT_ACCOUNT.to_vec())],
+                })
+            }
+            fn address_lookup_tables(
+                &mut self,
+                _addresses: &[Pubkey],
+            ) -> std::result::Result<Vec<AddressLookupTableAccount>, ()> {
+                unreachable!()
+            }
+        }
+        let mut client = ResolverClient::new(Stuck, PAYER);
+        assert!(matches!(
+            client.resolve_execute_vaa_v1(&PROGRAM_ID, &[]),
+            Err(ResolveError::InvalidReturnData(_))
+        ));
+    }
+
+    #[test]
+    fn test_resolve_max_iterations() {
+        let backend = IterativeBackend {
+            required: vec![Pubkey::new_unique(); 8],
+            lookup_table: Pubkey::new_unique(),
+            simulations: 0,
+            lookup_table_fetches: 0,
+        };
+        let mut client = ResolverClient::new(backend, PAYER).with_max_iterations(3);
+        assert!(matches!(
+            client.resolve_execute_vaa_v1(&PROGRAM_ID, &[]),
+            Err(ResolveError::MaxIterationsExceeded(3))
+        ));
+        assert_eq!(client.backend().simulations, 3);
+    }
+}
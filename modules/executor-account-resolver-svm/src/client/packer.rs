@@ -0,0 +1,322 @@
+//! Compilation of a substituted `InstructionGroup` into a v0 message which is checked against the
+//! runtime's transaction limits before it is sent.
+
+use std::fmt;
+
+use anchor_lang::prelude::*;
+use solana_hash::Hash;
+use solana_message::{v0, AddressLookupTableAccount, CompileError, VersionedMessage};
+
+use super::substitution::SubstitutedGroup;
+use crate::{size::short_vec_len, MAX_TRANSACTION_SIZE};
+
+/// The maximum number of accounts a transaction may lock, including those loaded from lookup tables.
+pub const MAX_TRANSACTION_ACCOUNT_LOCKS: usize = 64;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PackError {
+    /// The group refers to a lookup table whose contents were not provided.
+    MissingLookupTable(Pubkey),
+    /// The message could not be compiled, e.g. a lookup table has too many entries.
+    Compile(CompileError),
+    /// The serialized transaction exceeds `MAX_TRANSACTION_SIZE`.
+    TooLarge { size: usize },
+    /// The transaction locks more than `MAX_TRANSACTION_ACCOUNT_LOCKS` accounts.
+    TooManyAccountLocks { count: usize },
+    /// A required signer does not sign any instruction in the group.
+    UnexpectedSigner(Pubkey),
+    /// An instruction requires a signature from an account which was not provided as a signer.
+    MissingSigner(Pubkey),
+}
+
+impl fmt::Display for PackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PackError::MissingLookupTable(key) => write!(f, "lookup table {key} was not provided"),
+            PackError::Compile(err) => write!(f, "failed to compile message: {err}"),
+            PackError::TooLarge { size } => write!(
+                f,
+                "transaction is {size} bytes, exceeding the limit of {MAX_TRANSACTION_SIZE}"
+            ),
+            PackError::TooManyAccountLocks { count } => write!(
+                f,
+                "transaction locks {count} accounts, exceeding the limit of {MAX_TRANSACTION_ACCOUNT_LOCKS}"
+            ),
+            PackError::UnexpectedSigner(key) => {
+                write!(f, "{key} was provided as a signer but is not one")
+            }
+            PackError::MissingSigner(key) => write!(f, "{key} must sign but was not provided"),
+        }
+    }
+}
+
+impl std::error::Error for PackError {}
+
+/// A compiled message which fits within the runtime's transaction limits.
+#[derive(Debug, Clone)]
+pub struct PackedMessage {
+    pub message: VersionedMessage,
+    /// The exact serialized size of the signed transaction, in bytes.
+    pub size: usize,
+}
+
+/// Compiles `group`, preceded by its ComputeBudget instructions, into a v0 message paid for by `payer`.
+///
+/// `lookup_tables` must contain the contents of every table in `group.address_lookup_tables`,
+/// and may contain others. `signers` are the signers required in addition to `payer`,
+/// e.g. the keypairs returned by `Substitution::signers`.
+pub fn pack_group(
+    group: &SubstitutedGroup,
+    lookup_tables: &[AddressLookupTableAccount],
+    payer: &Pubkey,
+    signers: &[Pubkey],
+    recent_blockhash: Hash,
+) -> std::result::Result<PackedMessage, PackError> {
+    let lookup_tables = group
+        .address_lookup_tables
+        .iter()
+        .map(|key| {
+            lookup_tables
+                .iter()
+                .find(|table| table.key == *key)
+                .cloned()
+                .ok_or(PackError::MissingLookupTable(*key))
+        })
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    let message = v0::Message::try_compile(
+        payer,
+        &group.instructions_with_compute_budget(),
+        &lookup_tables,
+        recent_blockhash,
+    )
+    .map_err(PackError::Compile)?;
+
+    let message_signers =
+        &message.account_keys[..usize::from(message.header.num_required_signatures)];
+    if let Some(signer) = signers
+        .iter()
+        .find(|signer| !message_signers.contains(signer))
+    {
+        return Err(PackError::UnexpectedSigner(*signer));
+    }
+    if let Some(signer) = message_signers
+        .iter()
+        .find(|signer| *signer != payer && !signers.contains(signer))
+    {
+        return Err(PackError::MissingSigner(*signer));
+    }
+
+    let count = message.account_keys.len()
+        + message
+            .address_table_lookups
+            .iter()
+            .map(|lookup| lookup.writable_indexes.len() + lookup.readonly_indexes.len())
+            .sum::<usize>();
+    if count > MAX_TRANSACTION_ACCOUNT_LOCKS {
+        return Err(PackError::TooManyAccountLocks { count });
+    }
+
+    let message = VersionedMessage::V0(message);
+    let size = transaction_size(&message);
+    if size > MAX_TRANSACTION_SIZE {
+        return Err(PackError::TooLarge { size });
+    }
+    Ok(PackedMessage { message, size })
+}
+
+/// The serialized size of a transaction containing `message` and its signatures.
+pub fn transaction_size(message: &VersionedMessage) -> usize {
+    let signatures = usize::from(message.header().num_required_signatures);
+    short_vec_len(signatures) + signatures * 64 + message.serialize().len()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        client::substitution::{substitute_placeholders, SubstitutionContext},
+        estimate_transaction_size, InstructionGroup, InstructionGroups, SerializableInstruction,
+        RESOLVER_PUBKEY_KEYPAIR_00, RESOLVER_PUBKEY_PAYER,
+    };
+    use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+    use solana_signer::Signer;
+
+    const PAYER: Pubkey = Pubkey::new_from_array([1; 32]);
+
+    fn group(
+        instructions: Vec<Instruction>,
+        address_lookup_tables: Vec<Pubkey>,
+    ) -> SubstitutedGroup {
+        SubstitutedGroup {
+            instructions,
+            address_lookup_tables,
+            keypair_signers: vec![],
+            compute_unit_limit: None,
+            heap_frame_size: None,
+        }
+    }
+
+    fn instruction(accounts: Vec<AccountMeta>, data_len: usize) -> Instruction {
+        Instruction {
+            program_id: Pubkey::new_from_array([2; 32]),
+            accounts,
+            data: vec![0; data_len],
+        }
+    }
+
+    #[test]
+    fn test_pack_group_exact_size() {
+        let signer = Pubkey::new_unique();
+        let table = AddressLookupTableAccount {
+            key: Pubkey::new_unique(),
+            addresses: (0..10).map(|_| Pubkey::new_unique()).collect(),
+        };
+        let mut accounts = vec![
+            AccountMeta::new(PAYER, true),
+            AccountMeta::new(signer, true),
+        ];
+        accounts.extend(
+            table
+                .addresses
+                .iter()
+                .map(|key| AccountMeta::new_readonly(*key, false)),
+        );
+        let group = group(vec![instruction(accounts, 100)], vec![table.key]);
+        let packed =
+            pack_group(&group, &[table.clone()], &PAYER, &[signer], Hash::default()).unwrap();
+        let VersionedMessage::V0(message) = &packed.message else {
+            panic!("expected a v0 message");
+        };
+        assert_eq!(message.address_table_lookups.len(), 1);
+        assert_eq!(message.address_table_lookups[0].readonly_indexes.len(), 10);
+        // 1 (signature count) + 2 * 64 (signatures) + serialized message
+        assert_eq!(packed.size, 1 + 2 * 64 + packed.message.serialize().len());
+        assert_eq!(packed.size, 423);
+    }
+
+    #[test]
+    fn test_pack_group_too_large() {
+        let group = group(vec![instruction(vec![], 1000)], vec![]);
+        assert!(pack_group(&group, &[], &PAYER, &[], Hash::default()).is_ok());
+        let group = SubstitutedGroup {
+            instructions: vec![instruction(vec![], 1200)],
+            ..group
+        };
+        assert_eq!(
+            pack_group(&group, &[], &PAYER, &[], Hash::default()).unwrap_err(),
+            PackError::TooLarge { size: 1372 }
+        );
+    }
+
+    #[test]
+    fn test_pack_group_too_many_account_locks() {
+        let table = AddressLookupTableAccount {
+            key: Pubkey::new_unique(),
+            addresses: (0..63).map(|_| Pubkey::new_unique()).collect(),
+        };
+        let accounts = table
+            .addresses
+            .iter()
+            .map(|key| AccountMeta::new_readonly(*key, false))
+            .collect();
+        let group = group(vec![instruction(accounts, 0)], vec![table.key]);
+        // payer + program + 63 lookups
+        assert_eq!(
+            pack_group(&group, &[table], &PAYER, &[], Hash::default()).unwrap_err(),
+            PackError::TooManyAccountLocks { count: 65 }
+        );
+    }
+
+    #[test]
+    fn test_pack_group_missing_lookup_table() {
+        let key = Pubkey::new_unique();
+        let group = group(vec![instruction(vec![], 0)], vec![key]);
+        assert_eq!(
+            pack_group(&group, &[], &PAYER, &[], Hash::default()).unwrap_err(),
+            PackError::MissingLookupTable(key)
+        );
+    }
+
+    #[test]
+    fn test_pack_group_signers() {
+        let signer = Pubkey::new_unique();
+        let group = group(
+            vec![instruction(vec![AccountMeta::new(signer, true)], 0)],
+            vec![],
+        );
+        assert_eq!(
+            pack_group(&group, &[], &PAYER, &[], Hash::default()).unwrap_err(),
+            PackError::MissingSigner(signer)
+        );
+        let other = Pubkey::new_unique();
+        assert_eq!(
+            pack_group(&group, &[], &PAYER, &[signer, other], Hash::default()).unwrap_err(),
+            PackError::UnexpectedSigner(other)
+        );
+    }
+
+    #[test]
+    fn test_pack_group_matches_estimate() {
+        let table = AddressLookupTableAccount {
+            key: Pubkey::new_unique(),
+            addresses: (0..200).map(|_| Pubkey::new_unique()).collect(),
+        };
+        let mut accounts = vec![
+            AccountMeta::new(RESOLVER_PUBKEY_PAYER, true),
+            AccountMeta::new(RESOLVER_PUBKEY_KEYPAIR_00, true),
+            AccountMeta::new_readonly(Pubkey::new_unique(), false),
+        ];
+        accounts.extend(
+            table.addresses[..40]
+                .iter()
+                .enumerate()
+                .map(|(i, key)| AccountMeta {
+                    pubkey: *key,
+                    is_signer: false,
+                    is_writable: i % 3 == 0,
+                }),
+        );
+        let groups = InstructionGroups(vec![InstructionGroup {
+            instructions: vec![
+                SerializableInstruction::from(instruction(accounts.clone(), 300)),
+                SerializableInstruction::from(instruction(accounts[20..].to_vec(), 150)),
+            ],
+            address_lookup_tables: vec![table.key],
+        }]);
+        let estimate = estimate_transaction_size(&groups.0[0], &[&table.addresses]);
+
+        let mut context = SubstitutionContext::new(PAYER);
+        let substitution = substitute_placeholders(&groups.into(), &mut context).unwrap();
+        let signers: Vec<Pubkey> = substitution
+            .signers(&substitution.groups[0])
+            .iter()
+            .map(|keypair| keypair.pubkey())
+            .collect();
+        let packed = pack_group(
+            &substitution.groups[0],
+            &[table],
+            &PAYER,
+            &signers,
+            Hash::default(),
+        )
+        .unwrap();
+        assert_eq!(packed.size, estimate);
+    }
+
+    #[test]
+    fn test_pack_group_compute_budget() {
+        let group = SubstitutedGroup {
+            compute_unit_limit: Some(400_000),
+            ..group(vec![instruction(vec![], 0)], vec![])
+        };
+        let packed = pack_group(&group, &[], &PAYER, &[], Hash::default()).unwrap();
+        let VersionedMessage::V0(message) = &packed.message else {
+            panic!("expected a v0 message");
+        };
+        assert_eq!(message.instructions.len(), 2);
+        assert_eq!(
+            message.account_keys[usize::from(message.instructions[0].program_id_index)],
+            solana_compute_budget_interface::ID
+        );
+    }
+}
@@ -0,0 +1,497 @@
+//! A [`SimulationBackend`] which simulates via a Solana JSON-RPC endpoint, enabled by the `rpc` feature.
+//!
+//! Requests mirror the off-chain reference implementation in `tests/utils.ts`:
+//! `simulateTransaction` is called with `replaceRecentBlockhash`, without signature verification,
+//! and with the result account included in the returned post-simulation state.
+//!
+//! Usage:
+//!
+//! ```ignore
+//! let backend = RpcBackend::new("https://api.mainnet-beta.solana.com").with_commitment("confirmed");
+//! let groups = ResolverClient::new(backend, relayer.pubkey()).resolve_execute_vaa_v1(&program_id, &vaa_body)?;
+//! ```
+
+use std::{fmt, io};
+
+use anchor_lang::prelude::*;
+use base64::{prelude::BASE64_STANDARD, Engine};
+use serde_json::{json, Value};
+use solana_address_lookup_table_interface::state::AddressLookupTable;
+use solana_message::AddressLookupTableAccount;
+
+use super::{SimulationBackend, SimulationRequest, SimulationResult};
+
+#[derive(Debug)]
+pub enum RpcBackendError {
+    /// The HTTP request failed.
+    Transport(Box<ureq::Error>),
+    /// The HTTP response body could not be read.
+    Io(io::Error),
+    /// The transaction could not be serialized.
+    Serialize(bincode::Error),
+    /// The node returned a JSON-RPC error.
+    Rpc { code: i64, message: String },
+    /// The simulated transaction failed.
+    Simulation { err: Value, logs: Vec<String> },
+    /// The node returned a response which does not match the JSON-RPC spec.
+    InvalidResponse(&'static str),
+    /// A requested lookup table does not exist.
+    LookupTableNotFound(Pubkey),
+    /// A requested lookup table could not be deserialized.
+    InvalidLookupTable(Pubkey),
+}
+
+impl fmt::Display for RpcBackendError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RpcBackendError::Transport(err) => write!(f, "rpc transport error: {err}"),
+            RpcBackendError::Io(err) => write!(f, "rpc io error: {err}"),
+            RpcBackendError::Serialize(err) => write!(f, "failed to serialize transaction: {err}"),
+            RpcBackendError::Rpc { code, message } => write!(f, "rpc error {code}: {message}"),
+            RpcBackendError::Simulation { err, logs } => {
+                write!(f, "simulation failed: {err}")?;
+                for log in logs {
+                    write!(f, "\n  {log}")?;
+                }
+                Ok(())
+            }
+            RpcBackendError::InvalidResponse(reason) => write!(f, "invalid rpc response: {reason}"),
+            RpcBackendError::LookupTableNotFound(key) => {
+                write!(f, "lookup table {key} not found")
+            }
+            RpcBackendError::InvalidLookupTable(key) => write!(f, "invalid lookup table {key}"),
+        }
+    }
+}
+
+impl std::error::Error for RpcBackendError {}
+
+/// A [`SimulationBackend`] which calls `simulateTransaction` on a JSON-RPC endpoint.
+pub struct RpcBackend {
+    url: String,
+    agent: ureq::Agent,
+    commitment: Option<String>,
+    next_id: u64,
+}
+
+impl RpcBackend {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self::with_agent(url, ureq::Agent::new())
+    }
+
+    /// Uses a preconfigured `ureq::Agent`, e.g. with custom timeouts or a proxy.
+    pub fn with_agent(url: impl Into<String>, agent: ureq::Agent) -> Self {
+        RpcBackend {
+            url: url.into(),
+            agent,
+            commitment: None,
+            next_id: 1,
+        }
+    }
+
+    /// Sets the commitment used for simulation and account fetches, e.g. `"confirmed"`.
+    /// The node's default is used otherwise.
+    pub fn with_commitment(mut self, commitment: impl Into<String>) -> Self {
+        self.commitment = Some(commitment.into());
+        self
+    }
+
+    fn call(&mut self, method: &str, params: Value) -> std::result::Result<Value, RpcBackendError> {
+        let id = self.next_id;
+        self.next_id += 1;
+        let mut response: Value = self
+            .agent
+            .post(&self.url)
+            .send_json(json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "method": method,
+                "params": params,
+            }))
+            .map_err(|err| RpcBackendError::Transport(Box::new(err)))?
+            .into_json()
+            .map_err(RpcBackendError::Io)?;
+        if let Some(error) = response.get("error") {
+            return Err(RpcBackendError::Rpc {
+                code: error["code"].as_i64().unwrap_or_default(),
+                message: error["message"].as_str().unwrap_or_default().to_string(),
+            });
+        }
+        match response.get_mut("result") {
+            Some(result) => Ok(result.take()),
+            None => Err(RpcBackendError::InvalidResponse("missing result")),
+        }
+    }
+
+    /// Adds the configured commitment to a request config.
+    fn config(&self, mut config: Value) -> Value {
+        if let Some(commitment) = &self.commitment {
+            config["commitment"] = json!(commitment);
+        }
+        config
+    }
+}
+
+impl SimulationBackend for RpcBackend {
+    type Error = RpcBackendError;
+
+    fn simulate(
+        &mut self,
+        request: &SimulationRequest,
+    ) -> std::result::Result<SimulationResult, Self::Error> {
+        let transaction =
+            bincode::serialize(&request.transaction).map_err(RpcBackendError::Serialize)?;
+        let config = self.config(json!({
+            "encoding": "base64",
+            "replaceRecentBlockhash": true,
+            "sigVerify": false,
+            "accounts": {
+                "encoding": "base64",
+                "addresses": request.accounts.iter().map(Pubkey::to_string).collect::<Vec<_>>(),
+            },
+        }));
+        let result = self.call(
+            "simulateTransaction",
+            json!([BASE64_STANDARD.encode(transaction), config]),
+        )?;
+        let value = &result["value"];
+        let logs: Vec<String> = value["logs"]
+            .as_array()
+            .map(|logs| {
+                logs.iter()
+                    .filter_map(|log| log.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default();
+        if !value["err"].is_null() {
+            return Err(RpcBackendError::Simulation {
+                err: value["err"].clone(),
+                logs,
+            });
+        }
+        let return_data = parse_return_log(&logs, &request.program_id)?;
+        let accounts = match value["accounts"].as_array() {
+            Some(accounts) => accounts.iter().map(decode_account_data).collect(),
+            None => Ok(vec![None; request.accounts.len()]),
+        }?;
+        Ok(SimulationResult {
+            return_data,
+            accounts,
+        })
+    }
+
+    fn address_lookup_tables(
+        &mut self,
+        addresses: &[Pubkey],
+    ) -> std::result::Result<Vec<AddressLookupTableAccount>, Self::Error> {
+        let config = self.config(json!({ "encoding": "base64" }));
+        let result = self.call(
+            "getMultipleAccounts",
+            json!([
+                addresses.iter().map(Pubkey::to_string).collect::<Vec<_>>(),
+                config
+            ]),
+        )?;
+        let accounts = result["value"]
+            .as_array()
+            .ok_or(RpcBackendError::InvalidResponse("missing accounts"))?;
+        if accounts.len() != addresses.len() {
+            return Err(RpcBackendError::InvalidResponse(
+                "unexpected number of accounts",
+            ));
+        }
+        addresses
+            .iter()
+            .zip(accounts)
+            .map(|(key, account)| {
+                let data = decode_account_data(account)?
+                    .ok_or(RpcBackendError::LookupTableNotFound(*key))?;
+                let table = AddressLookupTable::deserialize(&data)
+                    .map_err(|_| RpcBackendError::InvalidLookupTable(*key))?;
+                Ok(AddressLookupTableAccount {
+                    key: *key,
+                    addresses: table.addresses.to_vec(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Finds the return data of `program_id` in simulation logs, i.e. the last `Program return: <program_id> <base64>` line.
+pub fn parse_return_log(
+    logs: &[String],
+    program_id: &Pubkey,
+) -> std::result::Result<Option<Vec<u8>>, RpcBackendError> {
+    let prefix = format!("Program return: {program_id} ");
+    logs.iter()
+        .rev()
+        .find_map(|log| log.strip_prefix(&prefix))
+        .map(|data| {
+            BASE64_STANDARD
+                .decode(data.trim_end())
+                .map_err(|_| RpcBackendError::InvalidResponse("invalid return data"))
+        })
+        .transpose()
+}
+
+/// Decodes an `UiAccount` with base64 encoding, or `null` for a non-existent account.
+fn decode_account_data(account: &Value) -> std::result::Result<Option<Vec<u8>>, RpcBackendError> {
+    if account.is_null() {
+        return Ok(None);
+    }
+    let data = account["data"][0]
+        .as_str()
+        .ok_or(RpcBackendError::InvalidResponse("missing account data"))?;
+    BASE64_STANDARD
+        .decode(data)
+        .map(Some)
+        .map_err(|_| RpcBackendError::InvalidResponse("invalid account data"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        client::ResolverClient, result_account_address, InstructionGroup, InstructionGroups,
+        MissingAccounts, Resolver, RESOLVER_RESULT_ACCOUNT,
+    };
+    use solana_address_lookup_table_interface::state::{LookupTableMeta, LOOKUP_TABLE_META_SIZE};
+    use solana_transaction::versioned::VersionedTransaction;
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpListener,
+        sync::mpsc,
+        thread,
+    };
+
+    /// Serves one canned JSON-RPC response per connection, recording each request body.
+    fn mock_rpc(responses: Vec<Value>) -> (String, mpsc::Receiver<Value>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            for response in responses {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream);
+                let mut content_length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line == "\r\n" {
+                        break;
+                    }
+                    if let Some((name, value)) = line.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            content_length = value.trim().parse().unwrap();
+                        }
+                    }
+                }
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).unwrap();
+                let request: Value = serde_json::from_slice(&body).unwrap();
+                let response = json!({
+                    "jsonrpc": "2.0",
+                    "id": request["id"],
+                    "result": response,
+                })
+                .to_string();
+                sender.send(request).unwrap();
+                write!(
+                    reader.get_mut(),
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    response.len(),
+                    response
+                )
+                .unwrap();
+            }
+        });
+        (url, receiver)
+    }
+
+    fn simulation_response(logs: Vec<String>, accounts: Value) -> Value {
+        json!({
+            "context": { "slot": 1 },
+            "value": {
+                "err": null,
+                "logs": logs,
+                "accounts": accounts,
+                "unitsConsumed": 1000,
+            },
+        })
+    }
+
+    fn return_log(program_id: &Pubkey, resolver: &Resolver<InstructionGroups>) -> String {
+        let mut data = vec![];
+        resolver.serialize(&mut data).unwrap();
+        format!(
+            "Program return: {program_id} {}",
+            BASE64_STANDARD.encode(data)
+        )
+    }
+
+    fn lookup_table_data(addresses: &[Pubkey]) -> Vec<u8> {
+        let mut data = vec![0; LOOKUP_TABLE_META_SIZE];
+        AddressLookupTable::overwrite_meta_data(&mut data, LookupTableMeta::default()).unwrap();
+        for address in addresses {
+            data.extend_from_slice(address.as_ref());
+        }
+        data
+    }
+
+    #[test]
+    fn test_parse_return_log() {
+        let program_id = Pubkey::new_unique();
+        let logs = vec![
+            format!("Program {program_id} invoke [1]"),
+            format!("Program return: {} AQ==", Pubkey::new_unique()),
+            format!("Program return: {program_id} Ag=="),
+            format!("Program {program_id} success"),
+        ];
+        assert_eq!(parse_return_log(&logs, &program_id).unwrap(), Some(vec![2]));
+        assert_eq!(parse_return_log(&logs[..2], &program_id).unwrap(), None);
+    }
+
+    #[test]
+    fn test_simulate_request_shape() {
+        let program_id = Pubkey::new_unique();
+        let payer = Pubkey::new_unique();
+        let result_account = result_account_address(&program_id);
+        let (url, requests) = mock_rpc(vec![simulation_response(
+            vec![return_log(
+                &program_id,
+                &Resolver::Resolved(InstructionGroups(vec![])),
+            )],
+            json!([null]),
+        )]);
+        let mut client =
+            ResolverClient::new(RpcBackend::new(url).with_commitment("confirmed"), payer);
+        let groups = client
+            .resolve_execute_vaa_v1(&program_id, &[1, 2, 3])
+            .unwrap();
+        assert!(groups.0.is_empty());
+
+        let request = requests.recv().unwrap();
+        assert_eq!(request["jsonrpc"], "2.0");
+        assert_eq!(request["method"], "simulateTransaction");
+        assert_eq!(
+            request["params"][1],
+            json!({
+                "encoding": "base64",
+                "replaceRecentBlockhash": true,
+                "sigVerify": false,
+                "accounts": {
+                    "encoding": "base64",
+                    "addresses": [result_account.to_string()],
+                },
+                "commitment": "confirmed",
+            })
+        );
+        let transaction: VersionedTransaction = bincode::deserialize(
+            &BASE64_STANDARD
+                .decode(request["params"][0].as_str().unwrap())
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(transaction.message.static_account_keys()[0], payer);
+        let instruction = &transaction.message.instructions()[0];
+        assert_eq!(
+            transaction.message.static_account_keys()[usize::from(instruction.program_id_index)],
+            program_id
+        );
+        assert_eq!(instruction.data[8..], [3, 0, 0, 0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_resolve_with_lookup_table_and_result_account() {
+        let program_id = Pubkey::new_unique();
+        let payer = Pubkey::new_unique();
+        let lookup_table = Pubkey::new_unique();
+        let table_addresses = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+        let resolved = Resolver::Resolved(InstructionGroups(vec![InstructionGroup {
+            instructions: vec![],
+            address_lookup_tables: vec![lookup_table],
+        }]));
+        let mut result_data = RESOLVER_RESULT_ACCOUNT.to_vec();
+        resolved.serialize(&mut result_data).unwrap();
+        let (url, requests) = mock_rpc(vec![
+            simulation_response(
+                vec![return_log(
+                    &program_id,
+                    &Resolver::Missing(MissingAccounts {
+                        accounts: vec![table_addresses[0]],
+                        address_lookup_tables: vec![lookup_table],
+                    }),
+                )],
+                json!([null]),
+            ),
+            json!({
+                "context": { "slot": 1 },
+                "value": [{
+                    "data": [BASE64_STANDARD.encode(lookup_table_data(&table_addresses)), "base64"],
+                    "executable": false,
+                    "lamports": 1,
+                    "owner": "AddressLookupTab1e1111111111111111111111111",
+                    "rentEpoch": 0,
+                    "space": 0,
+                }],
+            }),
+            simulation_response(
+                vec![return_log(&program_id, &Resolver::Account())],
+                json!([{
+                    "data": [BASE64_STANDARD.encode(&result_data), "base64"],
+                    "executable": false,
+                    "lamports": 1,
+                    "owner": program_id.to_string(),
+                    "rentEpoch": 0,
+                    "space": result_data.len(),
+                }]),
+            ),
+        ]);
+        let mut client = ResolverClient::new(RpcBackend::new(url), payer);
+        let groups = client.resolve_execute_vaa_v1(&program_id, &[]).unwrap();
+        assert_eq!(groups.0[0].address_lookup_tables, vec![lookup_table]);
+
+        assert_eq!(requests.recv().unwrap()["method"], "simulateTransaction");
+        let request = requests.recv().unwrap();
+        assert_eq!(request["method"], "getMultipleAccounts");
+        assert_eq!(
+            request["params"],
+            json!([[lookup_table.to_string()], { "encoding": "base64" }])
+        );
+        let request = requests.recv().unwrap();
+        let transaction: VersionedTransaction = bincode::deserialize(
+            &BASE64_STANDARD
+                .decode(request["params"][0].as_str().unwrap())
+                .unwrap(),
+        )
+        .unwrap();
+        let lookups = transaction.message.address_table_lookups().unwrap();
+        assert_eq!(lookups.len(), 1);
+        assert_eq!(lookups[0].account_key, lookup_table);
+        assert_eq!(lookups[0].readonly_indexes, vec![0]);
+    }
+
+    #[test]
+    fn test_simulation_error() {
+        let program_id = Pubkey::new_unique();
+        let (url, _requests) = mock_rpc(vec![json!({
+            "context": { "slot": 1 },
+            "value": {
+                "err": { "InstructionError": [0, { "Custom": 6000 }] },
+                "logs": ["Program log: AnchorError occurred."],
+                "accounts": null,
+            },
+        })]);
+        let mut client = ResolverClient::new(RpcBackend::new(url), Pubkey::new_unique());
+        match client.resolve_execute_vaa_v1(&program_id, &[]) {
+            Err(crate::client::ResolveError::Backend(RpcBackendError::Simulation {
+                err,
+                logs,
+            })) => {
+                assert_eq!(err["InstructionError"][1]["Custom"], 6000);
+                assert_eq!(logs, vec!["Program log: AnchorError occurred."]);
+            }
+            _ => panic!("expected a simulation error"),
+        }
+    }
+}
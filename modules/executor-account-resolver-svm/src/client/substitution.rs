@@ -0,0 +1,342 @@
+//! Replacement of the `RESOLVER_PUBKEY_*` placeholders in resolved instruction groups
+//! with the relayer's concrete accounts.
+
+use std::{collections::BTreeMap, fmt};
+
+use anchor_lang::{
+    prelude::*,
+    solana_program::instruction::{AccountMeta, Instruction},
+};
+use solana_compute_budget_interface::ComputeBudgetInstruction;
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+
+use crate::{InstructionGroupsV2, ResolverPlaceholder};
+
+/// The concrete accounts substituted for placeholders.
+pub struct SubstitutionContext<F = fn() -> Keypair> {
+    /// Replaces `RESOLVER_PUBKEY_PAYER`.
+    pub payer: Pubkey,
+    /// Replaces `RESOLVER_PUBKEY_POSTED_VAA`, if the VAA has been posted to the Core Bridge.
+    pub posted_vaa: Option<Pubkey>,
+    /// Replaces `RESOLVER_PUBKEY_SHIM_VAA_SIGS`, if the VAA's signatures have been posted to the Verify VAA Shim.
+    pub shim_vaa_sigs: Option<Pubkey>,
+    /// Generates the keypair for each distinct `ResolverPlaceholder::Keypair`.
+    pub new_keypair: F,
+}
+
+impl SubstitutionContext {
+    /// A context which generates fresh random keypairs.
+    pub fn new(payer: Pubkey) -> Self {
+        SubstitutionContext {
+            payer,
+            posted_vaa: None,
+            shim_vaa_sigs: None,
+            new_keypair: Keypair::new,
+        }
+    }
+}
+
+impl<F> SubstitutionContext<F> {
+    pub fn with_posted_vaa(mut self, posted_vaa: Pubkey) -> Self {
+        self.posted_vaa = Some(posted_vaa);
+        self
+    }
+
+    pub fn with_shim_vaa_sigs(mut self, shim_vaa_sigs: Pubkey) -> Self {
+        self.shim_vaa_sigs = Some(shim_vaa_sigs);
+        self
+    }
+
+    pub fn with_keypair_generator<G: FnMut() -> Keypair>(
+        self,
+        new_keypair: G,
+    ) -> SubstitutionContext<G> {
+        SubstitutionContext {
+            payer: self.payer,
+            posted_vaa: self.posted_vaa,
+            shim_vaa_sigs: self.shim_vaa_sigs,
+            new_keypair,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubstitutionError {
+    /// A group refers to `RESOLVER_PUBKEY_POSTED_VAA` but no posted VAA was provided.
+    MissingPostedVaa,
+    /// A group refers to `RESOLVER_PUBKEY_SHIM_VAA_SIGS` but no signatures account was provided.
+    MissingShimVaaSigs,
+}
+
+impl fmt::Display for SubstitutionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SubstitutionError::MissingPostedVaa => {
+                write!(f, "instructions require a posted VAA but none was provided")
+            }
+            SubstitutionError::MissingShimVaaSigs => write!(
+                f,
+                "instructions require a shim guardian signatures account but none was provided"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SubstitutionError {}
+
+/// An `InstructionGroupV2` with all placeholders replaced.
+#[derive(Debug, Clone)]
+pub struct SubstitutedGroup {
+    pub instructions: Vec<Instruction>,
+    pub address_lookup_tables: Vec<Pubkey>,
+    /// The `ResolverPlaceholder::Keypair` indexes which must sign
+    /// this group in addition to the payer, in ascending order.
+    pub keypair_signers: Vec<u8>,
+    pub compute_unit_limit: Option<u32>,
+    pub heap_frame_size: Option<u32>,
+}
+
+impl SubstitutedGroup {
+    /// The ComputeBudget instructions requesting this group's compute unit limit and heap frame size,
+    /// to be prepended to `instructions`.
+    pub fn compute_budget_instructions(&self) -> Vec<Instruction> {
+        self.compute_unit_limit
+            .map(ComputeBudgetInstruction::set_compute_unit_limit)
+            .into_iter()
+            .chain(
+                self.heap_frame_size
+                    .map(ComputeBudgetInstruction::request_heap_frame),
+            )
+            .collect()
+    }
+
+    /// `instructions` preceded by `compute_budget_instructions`.
+    pub fn instructions_with_compute_budget(&self) -> Vec<Instruction> {
+        let mut instructions = self.compute_budget_instructions();
+        instructions.extend_from_slice(&self.instructions);
+        instructions
+    }
+}
+
+/// The result of [`substitute_placeholders`].
+#[derive(Debug)]
+pub struct Substitution {
+    pub groups: Vec<SubstitutedGroup>,
+    /// The keypair generated for each keypair placeholder index, shared across all groups.
+    pub keypairs: BTreeMap<u8, Keypair>,
+}
+
+impl Substitution {
+    /// The generated keypairs which must sign `group`, in addition to the payer.
+    pub fn signers(&self, group: &SubstitutedGroup) -> Vec<&Keypair> {
+        group
+            .keypair_signers
+            .iter()
+            .map(|index| &self.keypairs[index])
+            .collect()
+    }
+}
+
+/// Replaces every placeholder in `groups` with the accounts from `context`.
+///
+/// Each distinct keypair placeholder is generated once and refers to the same keypair in all groups,
+/// so an account created with a new keypair in one group can be used by subsequent groups.
+pub fn substitute_placeholders<F: FnMut() -> Keypair>(
+    groups: &InstructionGroupsV2,
+    context: &mut SubstitutionContext<F>,
+) -> std::result::Result<Substitution, SubstitutionError> {
+    let mut keypairs: BTreeMap<u8, Keypair> = BTreeMap::new();
+    let mut substituted = Vec::with_capacity(groups.0.len());
+    for group in &groups.0 {
+        let mut keypair_signers = vec![];
+        let mut instructions = Vec::with_capacity(group.instructions.len());
+        for instruction in &group.instructions {
+            let mut accounts = Vec::with_capacity(instruction.accounts.len());
+            for meta in &instruction.accounts {
+                let pubkey = match ResolverPlaceholder::from_pubkey(&meta.pubkey) {
+                    Some(ResolverPlaceholder::Payer) => context.payer,
+                    Some(ResolverPlaceholder::PostedVaa) => context
+                        .posted_vaa
+                        .ok_or(SubstitutionError::MissingPostedVaa)?,
+                    Some(ResolverPlaceholder::ShimVaaSigs) => context
+                        .shim_vaa_sigs
+                        .ok_or(SubstitutionError::MissingShimVaaSigs)?,
+                    Some(ResolverPlaceholder::Keypair(index)) => {
+                        if meta.is_signer && !keypair_signers.contains(&index) {
+                            keypair_signers.push(index);
+                        }
+                        keypairs
+                            .entry(index)
+                            .or_insert_with(&mut context.new_keypair)
+                            .pubkey()
+                    }
+                    None => meta.pubkey,
+                };
+                accounts.push(AccountMeta {
+                    pubkey,
+                    is_signer: meta.is_signer,
+                    is_writable: meta.is_writable,
+                });
+            }
+            instructions.push(Instruction {
+                program_id: instruction.program_id,
+                accounts,
+                data: instruction.data.clone(),
+            });
+        }
+        keypair_signers.sort_unstable();
+        substituted.push(SubstitutedGroup {
+            instructions,
+            address_lookup_tables: group.address_lookup_tables.clone(),
+            keypair_signers,
+            compute_unit_limit: group.compute_unit_limit,
+            heap_frame_size: group.heap_frame_size,
+        });
+    }
+    Ok(Substitution {
+        groups: substituted,
+        keypairs,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        InstructionGroupV2, SerializableAccountMeta, SerializableInstruction,
+        RESOLVER_PUBKEY_KEYPAIR_01, RESOLVER_PUBKEY_KEYPAIR_03, RESOLVER_PUBKEY_PAYER,
+        RESOLVER_PUBKEY_POSTED_VAA, RESOLVER_PUBKEY_SHIM_VAA_SIGS,
+    };
+
+    fn meta(pubkey: Pubkey, is_signer: bool) -> SerializableAccountMeta {
+        SerializableAccountMeta {
+            pubkey,
+            is_signer,
+            is_writable: true,
+        }
+    }
+
+    fn group(accounts: Vec<SerializableAccountMeta>) -> InstructionGroupV2 {
+        InstructionGroupV2 {
+            instructions: vec![SerializableInstruction {
+                program_id: Pubkey::new_unique(),
+                accounts,
+                data: vec![1, 2, 3],
+            }],
+            address_lookup_tables: vec![],
+            compute_unit_limit: None,
+            heap_frame_size: None,
+        }
+    }
+
+    #[test]
+    fn test_substitute_placeholders() {
+        let payer = Pubkey::new_unique();
+        let posted_vaa = Pubkey::new_unique();
+        let shim_vaa_sigs = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        let groups = InstructionGroupsV2(vec![
+            group(vec![
+                meta(RESOLVER_PUBKEY_PAYER, true),
+                meta(RESOLVER_PUBKEY_KEYPAIR_03, true),
+                meta(RESOLVER_PUBKEY_KEYPAIR_01, true),
+                meta(RESOLVER_PUBKEY_SHIM_VAA_SIGS, false),
+                meta(other, false),
+            ]),
+            group(vec![
+                meta(RESOLVER_PUBKEY_POSTED_VAA, false),
+                meta(RESOLVER_PUBKEY_KEYPAIR_03, false),
+                meta(ResolverPlaceholder::Keypair(42).to_pubkey(), true),
+            ]),
+        ]);
+        let generated = std::cell::Cell::new(0);
+        let mut context = SubstitutionContext::new(payer)
+            .with_posted_vaa(posted_vaa)
+            .with_shim_vaa_sigs(shim_vaa_sigs)
+            .with_keypair_generator(|| {
+                generated.set(generated.get() + 1);
+                Keypair::new()
+            });
+        let substitution = substitute_placeholders(&groups, &mut context).unwrap();
+        assert_eq!(generated.get(), 3);
+        assert_eq!(substitution.keypairs.len(), 3);
+        let keypair_01 = substitution.keypairs[&1].pubkey();
+        let keypair_03 = substitution.keypairs[&3].pubkey();
+
+        let first = &substitution.groups[0];
+        assert_eq!(
+            first.instructions[0]
+                .accounts
+                .iter()
+                .map(|meta| meta.pubkey)
+                .collect::<Vec<_>>(),
+            vec![payer, keypair_03, keypair_01, shim_vaa_sigs, other]
+        );
+        assert!(first.instructions[0].accounts[1].is_signer);
+        assert_eq!(first.instructions[0].data, vec![1, 2, 3]);
+        assert_eq!(first.keypair_signers, vec![1, 3]);
+        assert_eq!(
+            substitution
+                .signers(first)
+                .iter()
+                .map(|keypair| keypair.pubkey())
+                .collect::<Vec<_>>(),
+            vec![keypair_01, keypair_03]
+        );
+
+        // the same keypair is reused but does not need to sign again
+        let second = &substitution.groups[1];
+        assert_eq!(second.instructions[0].accounts[0].pubkey, posted_vaa);
+        assert_eq!(second.instructions[0].accounts[1].pubkey, keypair_03);
+        assert_eq!(
+            second.instructions[0].accounts[2].pubkey,
+            substitution.keypairs[&42].pubkey()
+        );
+        assert_eq!(second.keypair_signers, vec![42]);
+    }
+
+    #[test]
+    fn test_substitute_placeholders_missing_context() {
+        let groups =
+            InstructionGroupsV2(vec![group(vec![meta(RESOLVER_PUBKEY_POSTED_VAA, false)])]);
+        let mut context = SubstitutionContext::new(Pubkey::new_unique());
+        assert_eq!(
+            substitute_placeholders(&groups, &mut context).unwrap_err(),
+            SubstitutionError::MissingPostedVaa
+        );
+        let groups = InstructionGroupsV2(vec![group(vec![meta(
+            RESOLVER_PUBKEY_SHIM_VAA_SIGS,
+            false,
+        )])]);
+        assert_eq!(
+            substitute_placeholders(&groups, &mut context).unwrap_err(),
+            SubstitutionError::MissingShimVaaSigs
+        );
+    }
+
+    #[test]
+    fn test_compute_budget_instructions() {
+        let mut groups = InstructionGroupsV2(vec![group(vec![])]);
+        let mut context = SubstitutionContext::new(Pubkey::new_unique());
+        let substitution = substitute_placeholders(&groups, &mut context).unwrap();
+        assert!(substitution.groups[0]
+            .compute_budget_instructions()
+            .is_empty());
+
+        groups.0[0].compute_unit_limit = Some(300_000);
+        groups.0[0].heap_frame_size = Some(64 * 1024);
+        let substitution = substitute_placeholders(&groups, &mut context).unwrap();
+        let instructions = substitution.groups[0].instructions_with_compute_budget();
+        assert_eq!(instructions.len(), 3);
+        assert_eq!(
+            instructions[0],
+            ComputeBudgetInstruction::set_compute_unit_limit(300_000)
+        );
+        assert_eq!(
+            instructions[1],
+            ComputeBudgetInstruction::request_heap_frame(64 * 1024)
+        );
+        assert_eq!(instructions[2], substitution.groups[0].instructions[0]);
+    }
+}
@@ -0,0 +1,683 @@
+//! Bounded decoding of resolver results, for clients reading return data or a `Resolver::Account()` or
+//! `Resolver::AccountAt` result account written by an untrusted resolver.
+
+use std::fmt;
+
+use anchor_lang::{prelude::*, solana_program::system_instruction::MAX_PERMITTED_DATA_LENGTH};
+
+use crate::{
+    InstructionGroup, InstructionGroupV2, InstructionGroups, InstructionGroupsV2, MissingAccounts,
+    Resolver, SerializableAccountMeta, SerializableInstruction, MAX_TRANSACTION_SIZE,
+    MAX_UNRESOLVABLE_REASON_LEN, RESOLVER_RESULT_ACCOUNT,
+};
+
+/// Limits on the lengths in a decoded `Resolver<InstructionGroups>`.
+///
+/// Each length prefix is checked against its limit before anything is allocated, so a resolver cannot make
+/// a relayer allocate more than the limits allow. The defaults admit any group which fits in a transaction.
+///
+/// Usage:
+///
+/// ```rust
+/// use executor_account_resolver_svm::{decode_return_data_with_limits, DecodeError, DecodeLimit, DecodeLimits};
+///
+/// let limits = DecodeLimits {
+///     max_groups: 4,
+///     ..DecodeLimits::default()
+/// };
+/// // `Resolver::Resolved` claiming 2^32 - 1 groups
+/// let err = decode_return_data_with_limits(&[0, 0xff, 0xff, 0xff, 0xff], &limits).unwrap_err();
+/// assert_eq!(
+///     err,
+///     DecodeError::LimitExceeded { limit: DecodeLimit::Groups, len: u32::MAX as usize, max: 4 }
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodeLimits {
+    pub max_groups: usize,
+    pub max_instructions_per_group: usize,
+    pub max_accounts_per_instruction: usize,
+    /// The maximum length of an instruction's data.
+    pub max_data_len: usize,
+    /// The maximum number of lookup tables of a group or of `MissingAccounts`.
+    pub max_lookup_tables: usize,
+    pub max_missing_accounts: usize,
+    /// The maximum length in bytes of the reason of a `Resolver::Unresolvable`.
+    pub max_reason_len: usize,
+    /// Whether to reject non-zero bytes after the result in a result account.
+    /// Off by default, as overwriting a longer result with a shorter one, e.g. with Anchor's `exit`, leaves stale bytes.
+    pub reject_trailing_data: bool,
+}
+
+impl Default for DecodeLimits {
+    fn default() -> Self {
+        DecodeLimits {
+            max_groups: 64,
+            // an instruction takes at least a program id index and two compact lengths
+            max_instructions_per_group: MAX_TRANSACTION_SIZE / 3,
+            // accounts are referred to by a u8 index
+            max_accounts_per_instruction: 256,
+            max_data_len: MAX_TRANSACTION_SIZE,
+            // a lookup table takes at least its address
+            max_lookup_tables: MAX_TRANSACTION_SIZE / 32,
+            max_missing_accounts: 256,
+            max_reason_len: MAX_UNRESOLVABLE_REASON_LEN,
+            reject_trailing_data: false,
+        }
+    }
+}
+
+/// A length limited by `DecodeLimits`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeLimit {
+    Groups,
+    InstructionsPerGroup,
+    AccountsPerInstruction,
+    DataLen,
+    LookupTables,
+    MissingAccounts,
+    ReasonLen,
+}
+
+impl fmt::Display for DecodeLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            DecodeLimit::Groups => "groups",
+            DecodeLimit::InstructionsPerGroup => "instructions in a group",
+            DecodeLimit::AccountsPerInstruction => "accounts in an instruction",
+            DecodeLimit::DataLen => "bytes of instruction data",
+            DecodeLimit::LookupTables => "lookup tables",
+            DecodeLimit::MissingAccounts => "missing accounts",
+            DecodeLimit::ReasonLen => "bytes of unresolvable reason",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The data is shorter than the `RESOLVER_RESULT_ACCOUNT` discriminator.
+    TooShort { len: usize },
+    /// The data is longer than any account can be.
+    TooLarge { len: usize },
+    /// The data does not start with the `RESOLVER_RESULT_ACCOUNT` discriminator.
+    InvalidDiscriminator([u8; 8]),
+    /// The data ends before the result is fully decoded.
+    Truncated { len: usize },
+    /// The result contains an invalid value, e.g. an unknown variant.
+    InvalidData(String),
+    /// A non-zero byte follows the result at `offset`.
+    TrailingData { offset: usize },
+    /// The result refers to a result account rather than containing the result.
+    NestedAccount,
+    /// A length prefix of the result exceeds its limit.
+    LimitExceeded {
+        limit: DecodeLimit,
+        len: usize,
+        max: usize,
+    },
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::TooShort { len } => write!(
+                f,
+                "result account is {len} bytes, shorter than its discriminator"
+            ),
+            DecodeError::TooLarge { len } => write!(
+                f,
+                "result account is {len} bytes, larger than the maximum account size"
+            ),
+            DecodeError::InvalidDiscriminator(discriminator) => write!(
+                f,
+                "result account has discriminator {discriminator:?}, expected {RESOLVER_RESULT_ACCOUNT:?}"
+            ),
+            DecodeError::Truncated { len } => {
+                write!(f, "result account ends after {len} bytes, before the result")
+            }
+            DecodeError::InvalidData(err) => write!(f, "invalid result: {err}"),
+            DecodeError::TrailingData { offset } => write!(
+                f,
+                "result account has a non-zero byte at offset {offset}, after the result"
+            ),
+            DecodeError::NestedAccount => {
+                write!(f, "result account refers to another result account")
+            }
+            DecodeError::LimitExceeded { limit, len, max } => {
+                write!(f, "result has {len} {limit}, more than the limit of {max}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+impl From<DecodeError> for anchor_lang::error::Error {
+    fn from(err: DecodeError) -> Self {
+        msg!("{}", err);
+        ErrorCode::AccountDidNotDeserialize.into()
+    }
+}
+
+/// Decodes the data of a result account written for `Resolver::Account()` or `Resolver::AccountAt`,
+/// with the default `DecodeLimits`.
+///
+/// The result account is never shrunk, so any bytes after the result are ignored like the TS client does.
+/// `ResultAccountWriter` zeroes them, so `DecodeLimits::reject_trailing_data` can be set for resolvers using it.
+///
+/// Usage:
+///
+/// ```ignore
+/// let data = rpc.get_account_data(&result_account_address(&program_id))?;
+/// let resolver = decode_result_account(&data)?;
+/// ```
+pub fn decode_result_account(
+    data: &[u8],
+) -> std::result::Result<Resolver<InstructionGroups>, DecodeError> {
+    decode_result_account_with_limits(data, &DecodeLimits::default())
+}
+
+/// Like `decode_result_account`, with the given `limits`.
+pub fn decode_result_account_with_limits(
+    data: &[u8],
+    limits: &DecodeLimits,
+) -> std::result::Result<Resolver<InstructionGroups>, DecodeError> {
+    if data.len() > RESOLVER_RESULT_ACCOUNT.len() + MAX_PERMITTED_DATA_LENGTH as usize {
+        return Err(DecodeError::TooLarge { len: data.len() });
+    }
+    result_account_body(data)?;
+    let mut decoder = Decoder {
+        data,
+        position: RESOLVER_RESULT_ACCOUNT.len(),
+        zero_padded: false,
+        limits,
+    };
+    let resolver = decoder.resolver()?;
+    let rest = &data[decoder.position..];
+    if let Some(position) = rest
+        .iter()
+        .position(|b| *b != 0)
+        .filter(|_| limits.reject_trailing_data)
+    {
+        return Err(DecodeError::TrailingData {
+            offset: decoder.position + position,
+        });
+    }
+    match resolver {
+        Resolver::Account() | Resolver::AccountAt(_) | Resolver::Partial { .. } => {
+            Err(DecodeError::NestedAccount)
+        }
+        resolver => Ok(resolver),
+    }
+}
+
+/// Decodes a resolver's return data with the given `limits`.
+///
+/// The runtime strips trailing zeros from return data, so the data is treated as if it were followed by zeros.
+pub fn decode_return_data_with_limits(
+    data: &[u8],
+    limits: &DecodeLimits,
+) -> std::result::Result<Resolver<InstructionGroups>, DecodeError> {
+    Decoder {
+        data,
+        position: 0,
+        zero_padded: true,
+        limits,
+    }
+    .resolver()
+}
+
+/// The result account data after its verified discriminator.
+pub(crate) fn result_account_body(data: &[u8]) -> std::result::Result<&[u8], DecodeError> {
+    let (discriminator, body) = data
+        .split_first_chunk::<8>()
+        .ok_or(DecodeError::TooShort { len: data.len() })?;
+    if discriminator != RESOLVER_RESULT_ACCOUNT {
+        return Err(DecodeError::InvalidDiscriminator(*discriminator));
+    }
+    Ok(body)
+}
+
+type DecodeResult<T> = std::result::Result<T, DecodeError>;
+
+/// A Borsh decoder of `Resolver<InstructionGroups>` which checks each length against its limit,
+/// and against the remaining data, before allocating.
+struct Decoder<'a> {
+    data: &'a [u8],
+    position: usize,
+    /// Whether the data is followed by implicit zeros, as return data is.
+    zero_padded: bool,
+    limits: &'a DecodeLimits,
+}
+
+impl Decoder<'_> {
+    fn read(&mut self, out: &mut [u8]) -> DecodeResult<()> {
+        let rest = self.data.get(self.position..).unwrap_or_default();
+        let n = rest.len().min(out.len());
+        if n < out.len() && !self.zero_padded {
+            return Err(DecodeError::Truncated {
+                len: self.data.len(),
+            });
+        }
+        out[..n].copy_from_slice(&rest[..n]);
+        out[n..].fill(0);
+        self.position += out.len();
+        Ok(())
+    }
+
+    fn array<const N: usize>(&mut self) -> DecodeResult<[u8; N]> {
+        let mut out = [0; N];
+        self.read(&mut out)?;
+        Ok(out)
+    }
+
+    fn u8(&mut self) -> DecodeResult<u8> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    fn u32(&mut self) -> DecodeResult<u32> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    fn bool(&mut self) -> DecodeResult<bool> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            b => Err(DecodeError::InvalidData(format!("invalid bool {b}"))),
+        }
+    }
+
+    fn option_u32(&mut self) -> DecodeResult<Option<u32>> {
+        match self.u8()? {
+            0 => Ok(None),
+            1 => Ok(Some(self.u32()?)),
+            tag => Err(DecodeError::InvalidData(format!(
+                "invalid Option tag {tag}"
+            ))),
+        }
+    }
+
+    fn pubkey(&mut self) -> DecodeResult<Pubkey> {
+        Ok(Pubkey::new_from_array(self.array()?))
+    }
+
+    /// A length prefix of elements which each take at least `min_size` bytes.
+    fn len(&mut self, limit: DecodeLimit, max: usize, min_size: usize) -> DecodeResult<usize> {
+        let len = self.u32()? as usize;
+        if len > max {
+            return Err(DecodeError::LimitExceeded { limit, len, max });
+        }
+        let remaining = self.data.len().saturating_sub(self.position);
+        if !self.zero_padded && len.saturating_mul(min_size) > remaining {
+            return Err(DecodeError::Truncated {
+                len: self.data.len(),
+            });
+        }
+        Ok(len)
+    }
+
+    fn vec<T>(
+        &mut self,
+        limit: DecodeLimit,
+        max: usize,
+        min_size: usize,
+        mut element: impl FnMut(&mut Self) -> DecodeResult<T>,
+    ) -> DecodeResult<Vec<T>> {
+        let len = self.len(limit, max, min_size)?;
+        let mut elements = Vec::with_capacity(len);
+        for _ in 0..len {
+            elements.push(element(self)?);
+        }
+        Ok(elements)
+    }
+
+    fn pubkeys(&mut self, limit: DecodeLimit, max: usize) -> DecodeResult<Vec<Pubkey>> {
+        self.vec(limit, max, 32, Self::pubkey)
+    }
+
+    fn instruction(&mut self) -> DecodeResult<SerializableInstruction> {
+        let program_id = self.pubkey()?;
+        let accounts = self.vec(
+            DecodeLimit::AccountsPerInstruction,
+            self.limits.max_accounts_per_instruction,
+            34,
+            |decoder| {
+                Ok(SerializableAccountMeta {
+                    pubkey: decoder.pubkey()?,
+                    is_signer: decoder.bool()?,
+                    is_writable: decoder.bool()?,
+                })
+            },
+        )?;
+        let mut data = vec![0; self.len(DecodeLimit::DataLen, self.limits.max_data_len, 1)?];
+        self.read(&mut data)?;
+        Ok(SerializableInstruction {
+            program_id,
+            accounts,
+            data,
+        })
+    }
+
+    fn group(&mut self) -> DecodeResult<InstructionGroup> {
+        Ok(InstructionGroup {
+            // an instruction takes at least its program id and two lengths
+            instructions: self.vec(
+                DecodeLimit::InstructionsPerGroup,
+                self.limits.max_instructions_per_group,
+                40,
+                Self::instruction,
+            )?,
+            address_lookup_tables: self
+                .pubkeys(DecodeLimit::LookupTables, self.limits.max_lookup_tables)?,
+        })
+    }
+
+    fn resolver(&mut self) -> DecodeResult<Resolver<InstructionGroups>> {
+        let max_groups = self.limits.max_groups;
+        match self.u8()? {
+            0 => Ok(Resolver::Resolved(InstructionGroups(self.vec(
+                DecodeLimit::Groups,
+                max_groups,
+                8,
+                Self::group,
+            )?))),
+            1 => Ok(Resolver::Missing(MissingAccounts {
+                accounts: self.pubkeys(
+                    DecodeLimit::MissingAccounts,
+                    self.limits.max_missing_accounts,
+                )?,
+                address_lookup_tables: self
+                    .pubkeys(DecodeLimit::LookupTables, self.limits.max_lookup_tables)?,
+            })),
+            2 => Ok(Resolver::Account()),
+            3 => Ok(Resolver::ResolvedV2(InstructionGroupsV2(self.vec(
+                DecodeLimit::Groups,
+                max_groups,
+                10,
+                |decoder| {
+                    let group = decoder.group()?;
+                    Ok(InstructionGroupV2 {
+                        compute_unit_limit: decoder.option_u32()?,
+                        heap_frame_size: decoder.option_u32()?,
+                        ..group.into()
+                    })
+                },
+            )?))),
+            4 => Ok(Resolver::Partial {
+                next_offset: self.u32()?,
+            }),
+            5 => Ok(Resolver::AccountAt(self.pubkey()?)),
+            6 => {
+                let code = self.u32()?;
+                let mut reason =
+                    vec![0; self.len(DecodeLimit::ReasonLen, self.limits.max_reason_len, 1)?];
+                self.read(&mut reason)?;
+                let reason = String::from_utf8(reason)
+                    .map_err(|err| DecodeError::InvalidData(err.to_string()))?;
+                Ok(Resolver::Unresolvable { code, reason })
+            }
+            tag => Err(DecodeError::InvalidData(format!(
+                "unknown Resolver variant {tag}"
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::golden::arb_resolver;
+    use proptest::{collection::vec, prelude::*};
+
+    fn account(resolver: &Resolver<InstructionGroups>) -> Vec<u8> {
+        let mut data = RESOLVER_RESULT_ACCOUNT.to_vec();
+        resolver.serialize(&mut data).unwrap();
+        data
+    }
+
+    fn resolved() -> Resolver<InstructionGroups> {
+        Resolver::Resolved(InstructionGroups(vec![InstructionGroup {
+            instructions: vec![],
+            address_lookup_tables: vec![Pubkey::new_from_array([1; 32])],
+        }]))
+    }
+
+    #[test]
+    fn test_decode_result_account() {
+        let mut data = account(&resolved());
+        let decoded = decode_result_account(&data).unwrap();
+        assert!(
+            matches!(decoded, Resolver::Resolved(InstructionGroups(groups)) if groups[0].address_lookup_tables == [Pubkey::new_from_array([1; 32])])
+        );
+        // realloc leaves trailing zeros
+        data.extend_from_slice(&[0; 100]);
+        assert!(decode_result_account(&data).is_ok());
+    }
+
+    #[test]
+    fn test_decode_result_account_errors() {
+        let data = account(&resolved());
+        assert_eq!(
+            decode_result_account(&data[..7]).err().unwrap(),
+            DecodeError::TooShort { len: 7 }
+        );
+        let mut invalid = data.clone();
+        invalid[0] ^= 1;
+        assert!(matches!(
+            decode_result_account(&invalid).err().unwrap(),
+            DecodeError::InvalidDiscriminator(_)
+        ));
+        for len in 8..data.len() {
+            assert_eq!(
+                decode_result_account(&data[..len]).err().unwrap(),
+                DecodeError::Truncated { len }
+            );
+        }
+        // stale bytes of a longer previous result are ignored unless rejected
+        let mut trailing = data.clone();
+        trailing.extend_from_slice(&[0, 0, 1]);
+        assert_eq!(decode_result_account(&trailing).unwrap(), resolved());
+        let limits = DecodeLimits {
+            reject_trailing_data: true,
+            ..DecodeLimits::default()
+        };
+        assert_eq!(
+            decode_result_account_with_limits(&trailing, &limits)
+                .err()
+                .unwrap(),
+            DecodeError::TrailingData {
+                offset: data.len() + 2
+            }
+        );
+        assert!(decode_result_account_with_limits(&data, &limits).is_ok());
+        let mut unknown = data.clone();
+        unknown[8] = 0xff;
+        assert!(matches!(
+            decode_result_account(&unknown).err().unwrap(),
+            DecodeError::InvalidData(_)
+        ));
+        assert_eq!(
+            decode_result_account(&account(&Resolver::Account()))
+                .err()
+                .unwrap(),
+            DecodeError::NestedAccount
+        );
+        assert_eq!(
+            decode_result_account(&vec![0; 8 + MAX_PERMITTED_DATA_LENGTH as usize + 1])
+                .err()
+                .unwrap(),
+            DecodeError::TooLarge {
+                len: 8 + MAX_PERMITTED_DATA_LENGTH as usize + 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_decode_limits() {
+        let limits = DecodeLimits {
+            max_groups: 1,
+            max_instructions_per_group: 1,
+            max_accounts_per_instruction: 1,
+            max_data_len: 1,
+            max_lookup_tables: 1,
+            max_missing_accounts: 1,
+            max_reason_len: 1,
+            reject_trailing_data: true,
+        };
+        let instruction = |accounts: usize, data: usize| SerializableInstruction {
+            program_id: Pubkey::default(),
+            accounts: vec![
+                SerializableAccountMeta {
+                    pubkey: Pubkey::default(),
+                    is_signer: false,
+                    is_writable: false,
+                };
+                accounts
+            ],
+            data: vec![1; data],
+        };
+        let group = |instructions: Vec<SerializableInstruction>, address_lookup_tables: usize| {
+            InstructionGroup {
+                instructions,
+                address_lookup_tables: vec![Pubkey::default(); address_lookup_tables],
+            }
+        };
+        let resolved = |groups| Resolver::Resolved(InstructionGroups(groups));
+        let decode = |resolver: &Resolver<InstructionGroups>| {
+            decode_result_account_with_limits(&account(resolver), &limits)
+        };
+        assert!(decode(&resolved(vec![group(vec![instruction(1, 1)], 1)])).is_ok());
+        for (resolver, limit) in [
+            (
+                resolved(vec![group(vec![], 0), group(vec![], 0)]),
+                DecodeLimit::Groups,
+            ),
+            (
+                resolved(vec![group(vec![instruction(0, 0), instruction(0, 0)], 0)]),
+                DecodeLimit::InstructionsPerGroup,
+            ),
+            (
+                resolved(vec![group(vec![instruction(2, 0)], 0)]),
+                DecodeLimit::AccountsPerInstruction,
+            ),
+            (
+                resolved(vec![group(vec![instruction(0, 2)], 0)]),
+                DecodeLimit::DataLen,
+            ),
+            (resolved(vec![group(vec![], 2)]), DecodeLimit::LookupTables),
+            (
+                Resolver::Missing(MissingAccounts {
+                    accounts: vec![Pubkey::default(); 2],
+                    address_lookup_tables: vec![],
+                }),
+                DecodeLimit::MissingAccounts,
+            ),
+            (
+                Resolver::Unresolvable {
+                    code: 1,
+                    reason: "ab".to_string(),
+                },
+                DecodeLimit::ReasonLen,
+            ),
+            (
+                Resolver::ResolvedV2(InstructionGroupsV2(vec![
+                    group(vec![], 0).into(),
+                    group(vec![], 0).into(),
+                ])),
+                DecodeLimit::Groups,
+            ),
+        ] {
+            assert_eq!(
+                decode(&resolver).err().unwrap(),
+                DecodeError::LimitExceeded {
+                    limit,
+                    len: 2,
+                    max: 1
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_decode_return_data_with_limits() {
+        let limits = DecodeLimits::default();
+        // trailing zeros are stripped from return data
+        assert_eq!(
+            decode_return_data_with_limits(&[], &limits).unwrap(),
+            Resolver::Resolved(InstructionGroups(vec![]))
+        );
+        assert_eq!(
+            decode_return_data_with_limits(&[2], &limits).unwrap(),
+            Resolver::Account()
+        );
+        // unresolvable reasons must be UTF-8
+        assert!(matches!(
+            decode_return_data_with_limits(&[6, 1, 0, 0, 0, 1, 0, 0, 0, 0xff], &limits),
+            Err(DecodeError::InvalidData(_))
+        ));
+        // an instruction claiming 2^32 - 1 bytes of zero padded data fails before allocating
+        let mut data = vec![0, 1, 0, 0, 0, 1, 0, 0, 0];
+        data.extend_from_slice(&[0; 32]);
+        data.extend_from_slice(&[0, 0, 0, 0, 0xff, 0xff, 0xff, 0xff]);
+        assert_eq!(
+            decode_return_data_with_limits(&data, &limits).unwrap_err(),
+            DecodeError::LimitExceeded {
+                limit: DecodeLimit::DataLen,
+                len: u32::MAX as usize,
+                max: MAX_TRANSACTION_SIZE
+            }
+        );
+    }
+
+    #[test]
+    fn test_decode_result_account_length_exceeds_data() {
+        // a length within the limits but longer than the remaining data fails before allocating
+        let mut data = RESOLVER_RESULT_ACCOUNT.to_vec();
+        data.extend_from_slice(&[1, 200, 0, 0, 0]);
+        assert_eq!(
+            decode_result_account(&data).unwrap_err(),
+            DecodeError::Truncated { len: data.len() }
+        );
+    }
+
+    /// Asserts that a successful bounded decode of `data` agrees with Borsh.
+    fn assert_matches_borsh(data: &[u8]) -> std::result::Result<(), TestCaseError> {
+        if let Ok(resolver) = decode_return_data_with_limits(data, &DecodeLimits::default()) {
+            let mut encoded = vec![];
+            resolver.serialize(&mut encoded).unwrap();
+            let mut padded = data.to_vec();
+            padded.resize(padded.len().max(encoded.len()), 0);
+            prop_assert_eq!(&padded[..encoded.len()], &encoded[..]);
+        }
+        Ok(())
+    }
+
+    proptest! {
+        #[test]
+        fn test_decode_random(data in vec(any::<u8>(), 0..512)) {
+            assert_matches_borsh(&data)?;
+            let mut account = RESOLVER_RESULT_ACCOUNT.to_vec();
+            account.extend_from_slice(&data);
+            if let Ok(resolver) = decode_result_account(&account) {
+                prop_assert_eq!(Resolver::deserialize(&mut &data[..]).unwrap(), resolver);
+            }
+        }
+
+        #[test]
+        fn test_decode_mutated(
+            resolver in arb_resolver(),
+            index in any::<prop::sample::Index>(),
+            byte in any::<u8>(),
+        ) {
+            let mut data = vec![];
+            resolver.serialize(&mut data).unwrap();
+            prop_assert_eq!(
+                decode_return_data_with_limits(&data, &DecodeLimits::default()).unwrap(),
+                resolver
+            );
+            let index = index.index(data.len());
+            data[index] = byte;
+            assert_matches_borsh(&data)?;
+        }
+    }
+}
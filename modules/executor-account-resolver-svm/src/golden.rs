@@ -0,0 +1,271 @@
+//! Golden wire-format fixtures and round-trip properties locking the serialization of `Resolver`.
+//!
+//! Each fixture in `fixtures/` is the exact Borsh encoding of the value built by the test of the same name.
+//! A failing fixture test means the wire format changed, which breaks every deployed resolver and relayer.
+//! New fixtures, e.g. for a new variant, can be written with `UPDATE_GOLDEN_FIXTURES=1 cargo test`,
+//! but existing fixtures must never be regenerated.
+
+use std::{fs, path::PathBuf};
+
+use anchor_lang::{prelude::*, solana_program::instruction::Instruction};
+use proptest::{collection::vec, prelude::*};
+
+use crate::{
+    InstructionGroup, InstructionGroupV2, InstructionGroups, InstructionGroupsV2, MissingAccounts,
+    Resolver, ResolverPlaceholder, SerializableAccountMeta, SerializableInstruction,
+    RESOLVER_PUBKEY_PAYER, RESOLVER_PUBKEY_POSTED_VAA, RESOLVER_PUBKEY_SHIM_VAA_SIGS,
+    UNRESOLVABLE_INVALID_RECIPIENT,
+};
+
+fn key(n: u8) -> Pubkey {
+    Pubkey::new_from_array([n; 32])
+}
+
+fn encode(resolver: &Resolver<InstructionGroups>) -> Vec<u8> {
+    let mut buffer = vec![];
+    resolver.serialize(&mut buffer).unwrap();
+    buffer
+}
+
+/// Asserts that `resolver` encodes to, and decodes from, exactly the bytes of `fixtures/<name>.bin`.
+fn assert_golden(name: &str, resolver: Resolver<InstructionGroups>) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("fixtures")
+        .join(format!("{name}.bin"));
+    let encoded = encode(&resolver);
+    if std::env::var_os("UPDATE_GOLDEN_FIXTURES").is_some() && !path.exists() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, &encoded).unwrap();
+    }
+    let golden = fs::read(&path).unwrap_or_else(|err| panic!("{}: {err}", path.display()));
+    assert_eq!(encoded, golden, "encoding of {name} changed");
+    assert_eq!(
+        Resolver::<InstructionGroups>::try_from_slice(&golden).unwrap(),
+        resolver,
+        "decoding of {name} changed"
+    );
+}
+
+/// An instruction referring to every placeholder.
+fn placeholder_instruction() -> SerializableInstruction {
+    let mut accounts = vec![
+        AccountMeta::new(RESOLVER_PUBKEY_PAYER, true),
+        AccountMeta::new_readonly(RESOLVER_PUBKEY_POSTED_VAA, false),
+        AccountMeta::new_readonly(RESOLVER_PUBKEY_SHIM_VAA_SIGS, false),
+    ];
+    for index in [0, 1, 9, 10, 42, 99] {
+        accounts.push(AccountMeta::new(
+            ResolverPlaceholder::Keypair(index).to_pubkey(),
+            index % 2 == 0,
+        ));
+    }
+    Instruction {
+        program_id: key(1),
+        accounts,
+        data: vec![0xde, 0xad, 0xbe, 0xef],
+    }
+    .into()
+}
+
+fn instruction(n: u8) -> SerializableInstruction {
+    SerializableInstruction {
+        program_id: key(n),
+        accounts: (0..n)
+            .map(|i| SerializableAccountMeta {
+                pubkey: key(n.wrapping_add(i)),
+                is_signer: i % 3 == 0,
+                is_writable: i % 2 == 0,
+            })
+            .collect(),
+        data: (0..n * 10).collect(),
+    }
+}
+
+#[test]
+fn test_golden_resolved_empty() {
+    assert_golden(
+        "resolved_empty",
+        Resolver::Resolved(InstructionGroups(vec![])),
+    );
+}
+
+#[test]
+fn test_golden_resolved_groups() {
+    assert_golden(
+        "resolved_groups",
+        Resolver::Resolved(InstructionGroups(vec![
+            InstructionGroup {
+                instructions: vec![placeholder_instruction()],
+                address_lookup_tables: vec![],
+            },
+            InstructionGroup {
+                instructions: (2..10).map(instruction).collect(),
+                address_lookup_tables: vec![key(200), key(201), key(202)],
+            },
+            InstructionGroup {
+                instructions: vec![],
+                address_lookup_tables: vec![key(203)],
+            },
+        ])),
+    );
+}
+
+#[test]
+fn test_golden_missing() {
+    assert_golden(
+        "missing",
+        Resolver::Missing(MissingAccounts {
+            accounts: vec![key(10), key(11), RESOLVER_PUBKEY_PAYER],
+            address_lookup_tables: vec![key(12)],
+        }),
+    );
+}
+
+#[test]
+fn test_golden_account() {
+    assert_golden("account", Resolver::Account());
+}
+
+#[test]
+fn test_golden_resolved_v2() {
+    assert_golden(
+        "resolved_v2",
+        Resolver::ResolvedV2(InstructionGroupsV2(vec![
+            InstructionGroupV2 {
+                instructions: vec![placeholder_instruction(), instruction(3)],
+                address_lookup_tables: vec![key(20)],
+                compute_unit_limit: Some(1_400_000),
+                heap_frame_size: Some(256 * 1024),
+            },
+            InstructionGroupV2 {
+                instructions: vec![instruction(1)],
+                address_lookup_tables: vec![],
+                compute_unit_limit: None,
+                heap_frame_size: None,
+            },
+        ])),
+    );
+}
+
+#[test]
+fn test_golden_partial() {
+    assert_golden(
+        "partial",
+        Resolver::Partial {
+            next_offset: 10_232,
+        },
+    );
+}
+
+#[test]
+fn test_golden_account_at() {
+    assert_golden("account_at", Resolver::AccountAt(key(30)));
+}
+
+#[test]
+fn test_golden_unresolvable() {
+    assert_golden(
+        "unresolvable",
+        Resolver::Unresolvable {
+            code: UNRESOLVABLE_INVALID_RECIPIENT,
+            reason: "recipient is not a token account".to_string(),
+        },
+    );
+}
+
+fn arb_pubkey() -> impl Strategy<Value = Pubkey> {
+    prop_oneof![
+        4 => any::<[u8; 32]>().prop_map(Pubkey::new_from_array),
+        1 => Just(RESOLVER_PUBKEY_PAYER),
+        1 => Just(RESOLVER_PUBKEY_POSTED_VAA),
+        1 => Just(RESOLVER_PUBKEY_SHIM_VAA_SIGS),
+        1 => (0..ResolverPlaceholder::KEYPAIR_COUNT)
+            .prop_map(|index| ResolverPlaceholder::Keypair(index).to_pubkey()),
+    ]
+}
+
+fn arb_instruction() -> impl Strategy<Value = SerializableInstruction> {
+    (
+        arb_pubkey(),
+        vec(
+            (arb_pubkey(), any::<bool>(), any::<bool>()).prop_map(
+                |(pubkey, is_signer, is_writable)| SerializableAccountMeta {
+                    pubkey,
+                    is_signer,
+                    is_writable,
+                },
+            ),
+            0..8,
+        ),
+        vec(any::<u8>(), 0..64),
+    )
+        .prop_map(|(program_id, accounts, data)| SerializableInstruction {
+            program_id,
+            accounts,
+            data,
+        })
+}
+
+fn arb_group() -> impl Strategy<Value = InstructionGroup> {
+    (vec(arb_instruction(), 0..4), vec(arb_pubkey(), 0..3)).prop_map(
+        |(instructions, address_lookup_tables)| InstructionGroup {
+            instructions,
+            address_lookup_tables,
+        },
+    )
+}
+
+fn arb_group_v2() -> impl Strategy<Value = InstructionGroupV2> {
+    (arb_group(), any::<Option<u32>>(), any::<Option<u32>>()).prop_map(
+        |(group, compute_unit_limit, heap_frame_size)| InstructionGroupV2 {
+            compute_unit_limit,
+            heap_frame_size,
+            ..group.into()
+        },
+    )
+}
+
+pub(crate) fn arb_resolver() -> impl Strategy<Value = Resolver<InstructionGroups>> {
+    prop_oneof![
+        vec(arb_group(), 0..4).prop_map(|groups| Resolver::Resolved(InstructionGroups(groups))),
+        (vec(arb_pubkey(), 0..8), vec(arb_pubkey(), 0..3)).prop_map(
+            |(accounts, address_lookup_tables)| Resolver::Missing(MissingAccounts {
+                accounts,
+                address_lookup_tables,
+            })
+        ),
+        Just(Resolver::Account()),
+        vec(arb_group_v2(), 0..4)
+            .prop_map(|groups| Resolver::ResolvedV2(InstructionGroupsV2(groups))),
+        any::<u32>().prop_map(|next_offset| Resolver::Partial { next_offset }),
+        arb_pubkey().prop_map(Resolver::AccountAt),
+        (any::<u32>(), ".{0,32}")
+            .prop_map(|(code, reason)| Resolver::Unresolvable { code, reason }),
+    ]
+}
+
+proptest! {
+    #[test]
+    fn test_resolver_round_trip(resolver in arb_resolver()) {
+        let encoded = encode(&resolver);
+        let decoded = Resolver::<InstructionGroups>::try_from_slice(&encoded).unwrap();
+        prop_assert_eq!(&decoded, &resolver);
+        prop_assert_eq!(encode(&decoded), encoded.clone());
+        // the encoding is exact, so no strict prefix decodes
+        prop_assert!(Resolver::<InstructionGroups>::try_from_slice(&encoded[..encoded.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_instruction_round_trip(instruction in arb_instruction()) {
+        let converted = SerializableInstruction::from(Instruction::from(instruction.clone()));
+        prop_assert_eq!(converted, instruction);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_resolver_json_round_trip(resolver in arb_resolver()) {
+        let json = serde_json::to_string(&resolver).unwrap();
+        let decoded: Resolver<InstructionGroups> = serde_json::from_str(&json).unwrap();
+        prop_assert_eq!(encode(&decoded), encode(&resolver));
+    }
+}
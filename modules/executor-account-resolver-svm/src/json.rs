@@ -0,0 +1,260 @@
+//! Serde helpers for the JSON representation of resolver results, enabled by the `serde` feature.
+//!
+//! The representation uses the camelCase variant and field names of the Anchor IDL, but is otherwise this crate's own.
+//! It is not the shape of the objects decoded by the Anchor TS client, which nests the fields of tuple variants and
+//! tuple structs under index keys, e.g. `{"resolved": {"0": {"0": [..]}}}`, and decodes data as a `Buffer`:
+//!
+//! ```json
+//! {
+//!   "resolved": [
+//!     {
+//!       "instructions": [
+//!         {
+//!           "programId": "11111111111111111111111111111111",
+//!           "accounts": [{ "pubkey": "...", "isSigner": true, "isWritable": true }],
+//!           "data": "AgAAAOgDAAAAAAAA"
+//!         }
+//!       ],
+//!       "addressLookupTables": []
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! - pubkeys are base58 strings and instruction data is standard base64
+//! - `Resolver` variants are tagged by their camelCase name, e.g. `{"missing": {"accounts": [..], "addressLookupTables": [..]}}`,
+//!   `{"account": {}}`, `{"partial": {"nextOffset": 1024}}`, `{"accountAt": "<pubkey>"}` or
+//!   `{"unresolvable": {"code": 3, "reason": "paused"}}`
+//! - `InstructionGroups` and `InstructionGroupsV2` are arrays of their groups, and tuple variants hold their only field
+//!   directly rather than under an index key
+
+use std::str::FromStr;
+
+use anchor_lang::prelude::Pubkey;
+use base64::{prelude::BASE64_STANDARD, Engine};
+use serde::{de::Error, ser::SerializeStruct, Deserialize, Deserializer, Serializer};
+
+/// A pubkey as a base58 string.
+pub(crate) mod pubkey {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(pubkey: &Pubkey, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(pubkey)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Pubkey, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Pubkey::from_str(&s).map_err(D::Error::custom)
+    }
+}
+
+/// Pubkeys as base58 strings.
+pub(crate) mod pubkeys {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(pubkeys: &[Pubkey], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(pubkeys.iter().map(ToString::to_string))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<Pubkey>, D::Error> {
+        Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .map(|s| Pubkey::from_str(s).map_err(D::Error::custom))
+            .collect()
+    }
+}
+
+/// Bytes as a standard base64 string.
+pub(crate) mod base64_bytes {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&BASE64_STANDARD.encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        BASE64_STANDARD.decode(s).map_err(D::Error::custom)
+    }
+}
+
+/// The fields of an empty tuple variant, e.g. `Resolver::Account()`, as an empty object like those of struct variants.
+pub(crate) mod empty {
+    use super::*;
+
+    #[derive(Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct Empty {}
+
+    pub fn serialize<S: Serializer>(serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_struct("Empty", 0)?.end()
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<(), D::Error> {
+        Empty::deserialize(deserializer).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use anchor_lang::{prelude::*, solana_program::instruction::Instruction};
+    use serde_json::json;
+
+    use crate::{
+        InstructionGroup, InstructionGroupV2, InstructionGroups, InstructionGroupsV2,
+        MissingAccounts, Resolver, RESOLVER_PUBKEY_PAYER,
+    };
+
+    fn resolved() -> Resolver<InstructionGroups> {
+        Resolver::Resolved(InstructionGroups(vec![InstructionGroup {
+            instructions: vec![Instruction {
+                program_id: Pubkey::default(),
+                accounts: vec![
+                    AccountMeta::new(RESOLVER_PUBKEY_PAYER, true),
+                    AccountMeta::new_readonly(Pubkey::new_from_array([1; 32]), false),
+                ],
+                data: vec![2, 0, 0, 0, 232, 3, 0, 0, 0, 0, 0, 0],
+            }
+            .into()],
+            address_lookup_tables: vec![Pubkey::new_from_array([2; 32])],
+        }]))
+    }
+
+    /// Round trips `resolver` through both Borsh and JSON, returning its JSON.
+    fn round_trip(resolver: &Resolver<InstructionGroups>) -> serde_json::Value {
+        let mut borsh = vec![];
+        resolver.serialize(&mut borsh).unwrap();
+        let value = serde_json::to_value(resolver).unwrap();
+        let from_json: Resolver<InstructionGroups> = serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(&from_json, resolver);
+        let mut from_json_borsh = vec![];
+        from_json.serialize(&mut from_json_borsh).unwrap();
+        assert_eq!(from_json_borsh, borsh);
+        value
+    }
+
+    #[test]
+    fn test_json_resolved() {
+        assert_eq!(
+            round_trip(&resolved()),
+            json!({
+                "resolved": [{
+                    "instructions": [{
+                        "programId": "11111111111111111111111111111111",
+                        "accounts": [
+                            {
+                                "pubkey": RESOLVER_PUBKEY_PAYER.to_string(),
+                                "isSigner": true,
+                                "isWritable": true,
+                            },
+                            {
+                                "pubkey": "4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi",
+                                "isSigner": false,
+                                "isWritable": false,
+                            },
+                        ],
+                        "data": "AgAAAOgDAAAAAAAA",
+                    }],
+                    "addressLookupTables": ["8qbHbw2BbbTHBW1sbeqakYXVKRQM8Ne7pLK7m6CVfeR"],
+                }],
+            })
+        );
+    }
+
+    #[test]
+    fn test_json_variants() {
+        let key = Pubkey::new_from_array([1; 32]);
+        assert_eq!(
+            round_trip(&Resolver::Missing(MissingAccounts {
+                accounts: vec![key],
+                address_lookup_tables: vec![],
+            })),
+            json!({ "missing": { "accounts": [key.to_string()], "addressLookupTables": [] } })
+        );
+        assert_eq!(round_trip(&Resolver::Account()), json!({ "account": {} }));
+        assert_eq!(
+            round_trip(&Resolver::Partial { next_offset: 1024 }),
+            json!({ "partial": { "nextOffset": 1024 } })
+        );
+        assert_eq!(
+            round_trip(&Resolver::AccountAt(key)),
+            json!({ "accountAt": key.to_string() })
+        );
+        assert_eq!(
+            round_trip(&Resolver::Unresolvable {
+                code: 3,
+                reason: "paused".to_string(),
+            }),
+            json!({ "unresolvable": { "code": 3, "reason": "paused" } })
+        );
+        assert_eq!(
+            round_trip(&Resolver::ResolvedV2(InstructionGroupsV2(vec![
+                InstructionGroupV2 {
+                    instructions: vec![],
+                    address_lookup_tables: vec![],
+                    compute_unit_limit: Some(400_000),
+                    heap_frame_size: None,
+                }
+            ]))),
+            json!({
+                "resolvedV2": [{
+                    "instructions": [],
+                    "addressLookupTables": [],
+                    "computeUnitLimit": 400_000,
+                    "heapFrameSize": null,
+                }],
+            })
+        );
+    }
+
+    #[test]
+    fn test_json_invalid() {
+        for value in [
+            json!({ "accountAt": "not a pubkey" }),
+            json!({ "missing": { "accounts": ["0"], "addressLookupTables": [] } }),
+            json!({ "resolved": [{ "instructions": [{
+                "programId": "11111111111111111111111111111111",
+                "accounts": [],
+                "data": "not base64!",
+            }], "addressLookupTables": [] }] }),
+            json!({ "account": { "unexpected": 1 } }),
+            json!({ "unresolved": [] }),
+        ] {
+            assert!(serde_json::from_value::<Resolver<InstructionGroups>>(value).is_err());
+        }
+    }
+
+    #[test]
+    fn test_json_not_ts_decoded() {
+        // `resolved()` in the shape of `JSON.stringify` of the object decoded by the Anchor TS client,
+        // which nests tuple fields under index keys and serializes data as a `Buffer`
+        let ts_decoded = json!({
+            "resolved": { "0": { "0": [{
+                "instructions": [{
+                    "programId": "11111111111111111111111111111111",
+                    "accounts": [
+                        {
+                            "pubkey": RESOLVER_PUBKEY_PAYER.to_string(),
+                            "isSigner": true,
+                            "isWritable": true,
+                        },
+                        {
+                            "pubkey": "4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi",
+                            "isSigner": false,
+                            "isWritable": false,
+                        },
+                    ],
+                    "data": { "type": "Buffer", "data": [2, 0, 0, 0, 232, 3, 0, 0, 0, 0, 0, 0] },
+                }],
+                "addressLookupTables": ["8qbHbw2BbbTHBW1sbeqakYXVKRQM8Ne7pLK7m6CVfeR"],
+            }] } },
+        });
+        assert!(serde_json::from_value::<Resolver<InstructionGroups>>(ts_decoded.clone()).is_err());
+        // the groups themselves differ only in the encoding of the data
+        let mut groups = ts_decoded["resolved"]["0"]["0"].clone();
+        groups[0]["instructions"][0]["data"] = json!("AgAAAOgDAAAAAAAA");
+        assert_eq!(json!({ "resolved": groups }), round_trip(&resolved()));
+    }
+}
@@ -1,4 +1,42 @@
 use anchor_lang::{prelude::*, solana_program::instruction::Instruction};
+use result_account::LenCounter;
+
+mod accounts;
+#[cfg(feature = "client")]
+pub mod client;
+mod decode;
+#[cfg(test)]
+mod golden;
+#[cfg(feature = "serde")]
+mod json;
+mod missing;
+mod require;
+mod result_account;
+mod router;
+mod size;
+mod stream;
+mod vaa;
+
+pub use accounts::ResolverAccounts;
+pub use decode::{
+    decode_result_account, decode_result_account_with_limits, decode_return_data_with_limits,
+    DecodeError, DecodeLimit, DecodeLimits,
+};
+#[cfg(feature = "derive")]
+pub use executor_account_resolver_svm_derive::ResolveAccounts;
+pub use missing::MissingCollector;
+pub use require::{
+    require_account, require_account_data, resolve_missing, ResolveResult, ResolverError,
+};
+pub use result_account::{
+    request_result_account_address, request_result_account_seeds, result_account_address,
+    result_offset, result_offset_marker, return_or_write, ResultAccountError, ResultAccountWriter,
+    RESOLVER_RESULT_OFFSET_PREFIX,
+};
+pub use router::{VaaHandler, VaaRouter};
+pub use size::estimate_transaction_size;
+pub use stream::{GroupWriter, InstructionGroupsWriter, InstructionWriter, StreamError};
+pub use vaa::{VaaBody, VaaBodyError};
 
 // NOTE: The `AnchorSerialize`d structs in this file MUST NOT break existing serialization/deserialization
 // compatibility as used by an instruction described in the spec and used in production.
@@ -9,12 +47,29 @@ use anchor_lang::{prelude::*, solana_program::instruction::Instruction};
 /// The hash input for `RESOLVER_EXECUTE_VAA_V1`.
 pub const RESOLVER_EXECUTE_VAA_V1_SEED: &[u8] = b"executor-account-resolver:execute-vaa-v1";
 /// The PDA seed for calculating the return account.
+/// Per-request result accounts append a request-specific seed, see `request_result_account_seeds`.
 pub const RESOLVER_RESULT_ACCOUNT_SEED: &[u8] = b"executor-account-resolver:result";
 /// The initial size for an empty resolver result `Resolver::Resolved(InstructionGroups(vec![]))`
 ///
 /// Usage: `space = 8 + RESOLVER_RESULT_ACCOUNT_INIT_SIZE`
 pub const RESOLVER_RESULT_ACCOUNT_INIT_SIZE: usize = 5;
 
+// limits
+/// The maximum serialized size of a transaction, including its signatures.
+pub const MAX_TRANSACTION_SIZE: usize = 1232;
+/// The maximum length in bytes of the reason of a `Resolver::Unresolvable`.
+pub const MAX_UNRESOLVABLE_REASON_LEN: usize = 256;
+
+// unresolvable codes
+/// `Resolver::Unresolvable` code for a VAA payload which the resolver does not support.
+pub const UNRESOLVABLE_UNSUPPORTED_PAYLOAD: u32 = 1;
+/// `Resolver::Unresolvable` code for a VAA whose recipient is invalid, e.g. not an account of the program.
+pub const UNRESOLVABLE_INVALID_RECIPIENT: u32 = 2;
+/// `Resolver::Unresolvable` code for a VAA which the program will not execute because it is paused.
+pub const UNRESOLVABLE_PAUSED: u32 = 3;
+/// The first `Resolver::Unresolvable` code for resolver-specific reasons, which never collide with the codes above.
+pub const UNRESOLVABLE_CUSTOM: u32 = 1000;
+
 // discriminators
 /// Discriminator for resolving the instructions for executing a v1 VAA.
 ///
@@ -89,18 +144,175 @@ pub const RESOLVER_PUBKEY_KEYPAIR_08: Pubkey =
 pub const RESOLVER_PUBKEY_KEYPAIR_09: Pubkey =
     Pubkey::new_from_array(*b"keypair_09_000000000000000000000");
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+/// A typed account placeholder, convertible to and from its padded `Pubkey` representation.
+///
+/// Unlike the `RESOLVER_PUBKEY_KEYPAIR_nn` constants, which only cover `00` through `09`,
+/// `Keypair` supports any index from `00` through `99`.
+///
+/// Usage:
+///
+/// ```rust
+/// use executor_account_resolver_svm::{ResolverPlaceholder, RESOLVER_PUBKEY_KEYPAIR_01};
+///
+/// let new_account = ResolverPlaceholder::Keypair(42).to_pubkey();
+/// assert_eq!(
+///     ResolverPlaceholder::from_pubkey(&new_account),
+///     Some(ResolverPlaceholder::Keypair(42))
+/// );
+/// assert_eq!(ResolverPlaceholder::Keypair(1).to_pubkey(), RESOLVER_PUBKEY_KEYPAIR_01);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ResolverPlaceholder {
+    /// `RESOLVER_PUBKEY_PAYER`
+    Payer,
+    /// `RESOLVER_PUBKEY_POSTED_VAA`
+    PostedVaa,
+    /// `RESOLVER_PUBKEY_SHIM_VAA_SIGS`
+    ShimVaaSigs,
+    /// `keypair_nn_000000000000000000000`, where `nn` is the zero-padded index.
+    Keypair(u8),
+}
+
+impl ResolverPlaceholder {
+    /// The number of distinct keypair placeholders, i.e. indexes `00` through `99`.
+    pub const KEYPAIR_COUNT: u8 = 100;
+
+    const KEYPAIR_PREFIX: &'static [u8; 8] = b"keypair_";
+
+    /// Returns the placeholder's pubkey.
+    ///
+    /// Panics if a `Keypair` index is not less than `KEYPAIR_COUNT`.
+    pub const fn to_pubkey(self) -> Pubkey {
+        match self {
+            ResolverPlaceholder::Payer => RESOLVER_PUBKEY_PAYER,
+            ResolverPlaceholder::PostedVaa => RESOLVER_PUBKEY_POSTED_VAA,
+            ResolverPlaceholder::ShimVaaSigs => RESOLVER_PUBKEY_SHIM_VAA_SIGS,
+            ResolverPlaceholder::Keypair(index) => {
+                assert!(
+                    index < Self::KEYPAIR_COUNT,
+                    "keypair placeholder index must be less than 100"
+                );
+                let mut bytes = [b'0'; 32];
+                let mut i = 0;
+                while i < Self::KEYPAIR_PREFIX.len() {
+                    bytes[i] = Self::KEYPAIR_PREFIX[i];
+                    i += 1;
+                }
+                bytes[8] = b'0' + index / 10;
+                bytes[9] = b'0' + index % 10;
+                bytes[10] = b'_';
+                Pubkey::new_from_array(bytes)
+            }
+        }
+    }
+
+    /// Classifies a pubkey, returning `None` if it is not a placeholder.
+    pub fn from_pubkey(pubkey: &Pubkey) -> Option<Self> {
+        if *pubkey == RESOLVER_PUBKEY_PAYER {
+            return Some(ResolverPlaceholder::Payer);
+        }
+        if *pubkey == RESOLVER_PUBKEY_POSTED_VAA {
+            return Some(ResolverPlaceholder::PostedVaa);
+        }
+        if *pubkey == RESOLVER_PUBKEY_SHIM_VAA_SIGS {
+            return Some(ResolverPlaceholder::ShimVaaSigs);
+        }
+        let bytes = pubkey.as_ref();
+        let (tens, ones) = (bytes[8], bytes[9]);
+        if bytes.starts_with(Self::KEYPAIR_PREFIX)
+            && tens.is_ascii_digit()
+            && ones.is_ascii_digit()
+            && bytes[10] == b'_'
+            && bytes[11..].iter().all(|b| *b == b'0')
+        {
+            return Some(ResolverPlaceholder::Keypair(
+                (tens - b'0') * 10 + (ones - b'0'),
+            ));
+        }
+        None
+    }
+}
+
+impl From<ResolverPlaceholder> for Pubkey {
+    fn from(placeholder: ResolverPlaceholder) -> Self {
+        placeholder.to_pubkey()
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
 pub struct InstructionGroups(pub Vec<InstructionGroup>);
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
 pub struct InstructionGroup {
     pub instructions: Vec<SerializableInstruction>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::json::pubkeys"))]
     pub address_lookup_tables: Vec<Pubkey>,
 }
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+/// `InstructionGroups` with compute budget hints, returned via `Resolver::ResolvedV2`.
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct InstructionGroupsV2(pub Vec<InstructionGroupV2>);
+
+/// An `InstructionGroup` with the compute budget the relayer should request for its transaction.
+/// `None` leaves the runtime default in place.
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub struct InstructionGroupV2 {
+    pub instructions: Vec<SerializableInstruction>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::json::pubkeys"))]
+    pub address_lookup_tables: Vec<Pubkey>,
+    /// The compute unit limit for the transaction, requested via `SetComputeUnitLimit`.
+    pub compute_unit_limit: Option<u32>,
+    /// The heap frame size in bytes, requested via `RequestHeapFrame`. Must be a multiple of 1024.
+    pub heap_frame_size: Option<u32>,
+}
+
+impl From<InstructionGroup> for InstructionGroupV2 {
+    fn from(group: InstructionGroup) -> Self {
+        InstructionGroupV2 {
+            instructions: group.instructions,
+            address_lookup_tables: group.address_lookup_tables,
+            compute_unit_limit: None,
+            heap_frame_size: None,
+        }
+    }
+}
+
+impl From<InstructionGroups> for InstructionGroupsV2 {
+    fn from(groups: InstructionGroups) -> Self {
+        InstructionGroupsV2(groups.0.into_iter().map(Into::into).collect())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
 pub struct SerializableInstruction {
+    #[cfg_attr(feature = "serde", serde(with = "crate::json::pubkey"))]
     pub program_id: Pubkey,
     pub accounts: Vec<SerializableAccountMeta>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::json::base64_bytes"))]
     pub data: Vec<u8>,
 }
 
@@ -118,8 +330,28 @@ impl From<Instruction> for SerializableInstruction {
     }
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+impl From<SerializableInstruction> for Instruction {
+    fn from(instruction: SerializableInstruction) -> Self {
+        Instruction {
+            program_id: instruction.program_id,
+            accounts: instruction
+                .accounts
+                .into_iter()
+                .map(|account_meta| account_meta.into())
+                .collect(),
+            data: instruction.data,
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
 pub struct SerializableAccountMeta {
+    #[cfg_attr(feature = "serde", serde(with = "crate::json::pubkey"))]
     pub pubkey: Pubkey,
     pub is_signer: bool,
     pub is_writable: bool,
@@ -135,22 +367,76 @@ impl From<AccountMeta> for SerializableAccountMeta {
     }
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+impl From<SerializableAccountMeta> for AccountMeta {
+    fn from(account_meta: SerializableAccountMeta) -> Self {
+        AccountMeta {
+            pubkey: account_meta.pubkey,
+            is_signer: account_meta.is_signer,
+            is_writable: account_meta.is_writable,
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase", rename_all_fields = "camelCase")
+)]
 pub enum Resolver<T> {
     Resolved(T),
     Missing(MissingAccounts),
+    #[cfg_attr(feature = "serde", serde(with = "crate::json::empty"))]
     Account(),
+    /// Like `Resolved`, with compute budget hints for each group.
+    ResolvedV2(InstructionGroupsV2),
+    /// The serialized result is too large to write in one transaction. The bytes before `next_offset`
+    /// have been written to the result account and the resolver should be called again with
+    /// `result_offset_marker(next_offset)` as its last remaining account.
+    /// See `ResultAccountWriter::write_paginated`.
+    Partial {
+        next_offset: u32,
+    },
+    /// Like `Account`, but the result was written to the given account instead of the canonical result account,
+    /// e.g. the per-request result account at `request_result_account_address`.
+    /// See `ResultAccountWriter::write_at`.
+    AccountAt(#[cfg_attr(feature = "serde", serde(with = "crate::json::pubkey"))] Pubkey),
+    /// The request can never be resolved, e.g. its payload is unsupported, so relayers should stop retrying it.
+    /// `code` is one of the `UNRESOLVABLE_*` codes and `reason` is a short description. See `unresolvable`.
+    Unresolvable {
+        code: u32,
+        reason: String,
+    },
+}
+
+impl<T: AnchorSerialize> Resolver<T> {
+    /// The length of the serialized result, e.g. to check whether it fits in `MAX_RETURN_DATA`.
+    /// See `return_or_write` for choosing between return data and the result account.
+    pub fn serialized_len(&self) -> usize {
+        let mut counter = LenCounter(0);
+        self.serialize(&mut counter)
+            .expect("counting serialized bytes cannot fail");
+        counter.0
+    }
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
 pub struct MissingAccounts {
+    #[cfg_attr(feature = "serde", serde(with = "crate::json::pubkeys"))]
     pub accounts: Vec<Pubkey>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::json::pubkeys"))]
     pub address_lookup_tables: Vec<Pubkey>,
 }
 
 // helpers
 /// A helper function for finding a pubkey in `remaining_accounts`.
-/// Depending on the use case, it may be more efficient to assume accounts at certain indexes.
+/// Depending on the use case, it may be more efficient to assume accounts at certain indexes,
+/// or to build a `ResolverAccounts` index when looking up many accounts.
 ///
 /// Usage:
 ///
@@ -211,7 +497,7 @@ pub fn find_account<'c, 'info>(
 /// ```
 ///
 /// Depending on the use case, it may be more efficient to resolve multiple missing accounts,
-/// in which case `Resolver::Missing(MissingAccounts)` can be constructed manually.
+/// in which case `Resolver::Missing(MissingAccounts)` can be constructed manually or with a `MissingCollector`.
 ///
 pub fn missing_account(pubkey: Pubkey) -> Resolver<InstructionGroups> {
     Resolver::Missing(MissingAccounts {
@@ -220,6 +506,43 @@ pub fn missing_account(pubkey: Pubkey) -> Resolver<InstructionGroups> {
     })
 }
 
+/// A helper function for resolving to `Resolver::Unresolvable`, with `reason` truncated to
+/// `MAX_UNRESOLVABLE_REASON_LEN` bytes.
+///
+/// Usage:
+///
+/// ```rust
+/// use anchor_lang::prelude::*;
+/// use executor_account_resolver_svm::{
+///     unresolvable, InstructionGroups, Resolver, VaaBody, RESOLVER_EXECUTE_VAA_V1,
+///     UNRESOLVABLE_UNSUPPORTED_PAYLOAD,
+/// };
+///
+/// #[derive(Accounts)]
+/// pub struct Resolve {}
+///
+/// #[instruction(discriminator = &RESOLVER_EXECUTE_VAA_V1)]
+/// pub fn resolve_execute_vaa_v1(ctx: Context<Resolve>, vaa_body: Vec<u8>) -> Result<Resolver<InstructionGroups>> {
+///     let vaa = VaaBody::parse(&vaa_body)?;
+///     if !vaa.payload().starts_with(b"\x01") {
+///         return Ok(unresolvable(UNRESOLVABLE_UNSUPPORTED_PAYLOAD, "unsupported payload type"));
+///     }
+///     Ok(Resolver::Resolved(InstructionGroups(vec![
+///         // build your `InstructionGroup`s here
+///     ])))
+/// }
+/// ```
+pub fn unresolvable(code: u32, reason: &str) -> Resolver<InstructionGroups> {
+    let mut len = reason.len().min(MAX_UNRESOLVABLE_REASON_LEN);
+    while !reason.is_char_boundary(len) {
+        len -= 1;
+    }
+    Resolver::Unresolvable {
+        code,
+        reason: reason[..len].to_string(),
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -242,6 +565,66 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_resolver_placeholder_round_trip() {
+        let constants = [
+            (ResolverPlaceholder::Payer, RESOLVER_PUBKEY_PAYER),
+            (ResolverPlaceholder::PostedVaa, RESOLVER_PUBKEY_POSTED_VAA),
+            (
+                ResolverPlaceholder::ShimVaaSigs,
+                RESOLVER_PUBKEY_SHIM_VAA_SIGS,
+            ),
+            (ResolverPlaceholder::Keypair(0), RESOLVER_PUBKEY_KEYPAIR_00),
+            (ResolverPlaceholder::Keypair(1), RESOLVER_PUBKEY_KEYPAIR_01),
+            (ResolverPlaceholder::Keypair(2), RESOLVER_PUBKEY_KEYPAIR_02),
+            (ResolverPlaceholder::Keypair(3), RESOLVER_PUBKEY_KEYPAIR_03),
+            (ResolverPlaceholder::Keypair(4), RESOLVER_PUBKEY_KEYPAIR_04),
+            (ResolverPlaceholder::Keypair(5), RESOLVER_PUBKEY_KEYPAIR_05),
+            (ResolverPlaceholder::Keypair(6), RESOLVER_PUBKEY_KEYPAIR_06),
+            (ResolverPlaceholder::Keypair(7), RESOLVER_PUBKEY_KEYPAIR_07),
+            (ResolverPlaceholder::Keypair(8), RESOLVER_PUBKEY_KEYPAIR_08),
+            (ResolverPlaceholder::Keypair(9), RESOLVER_PUBKEY_KEYPAIR_09),
+        ];
+        for (placeholder, pubkey) in constants {
+            assert_eq!(placeholder.to_pubkey(), pubkey);
+            assert_eq!(ResolverPlaceholder::from_pubkey(&pubkey), Some(placeholder));
+        }
+        for index in 0..ResolverPlaceholder::KEYPAIR_COUNT {
+            let pubkey = ResolverPlaceholder::Keypair(index).to_pubkey();
+            assert_eq!(
+                pubkey.to_bytes(),
+                *format!("keypair_{index:02}_000000000000000000000").as_bytes()
+            );
+            assert_eq!(
+                ResolverPlaceholder::from_pubkey(&pubkey),
+                Some(ResolverPlaceholder::Keypair(index))
+            );
+        }
+    }
+
+    #[test]
+    fn test_resolver_placeholder_rejects_non_placeholders() {
+        for bytes in [
+            *b"keypair_0a_000000000000000000000",
+            *b"keypair_00-000000000000000000000",
+            *b"keypair_00_000000000000000000001",
+            *b"keypair-00_000000000000000000000",
+            *b"payer_00000000000000000000000001",
+        ] {
+            assert_eq!(
+                ResolverPlaceholder::from_pubkey(&Pubkey::new_from_array(bytes)),
+                None
+            );
+        }
+        assert_eq!(ResolverPlaceholder::from_pubkey(&Pubkey::default()), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "keypair placeholder index must be less than 100")]
+    fn test_resolver_placeholder_keypair_out_of_range() {
+        ResolverPlaceholder::Keypair(100).to_pubkey();
+    }
+
     #[test]
     fn test_resolver_resolved_empty_serialization() {
         let resolved = Resolver::Resolved(InstructionGroups(vec![]));
@@ -271,4 +654,103 @@ mod test {
         assert_eq!(buffer.len(), 1);
         assert_eq!(buffer, [2])
     }
+
+    #[test]
+    fn test_resolver_resolved_v2_serialization() {
+        let resolved: Resolver<InstructionGroups> =
+            Resolver::ResolvedV2(InstructionGroupsV2(vec![InstructionGroupV2 {
+                instructions: vec![],
+                address_lookup_tables: vec![],
+                compute_unit_limit: Some(200_000),
+                heap_frame_size: None,
+            }]));
+        let mut buffer: Vec<u8> = Vec::new();
+        resolved.serialize(&mut buffer).unwrap();
+        assert_eq!(
+            buffer,
+            [3, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0x40, 0x0d, 0x03, 0, 0]
+        )
+    }
+
+    #[test]
+    fn test_resolver_partial_serialization() {
+        let partial: Resolver<InstructionGroups> = Resolver::Partial {
+            next_offset: 0x01020304,
+        };
+        let mut buffer: Vec<u8> = Vec::new();
+        partial.serialize(&mut buffer).unwrap();
+        assert_eq!(buffer, [4, 4, 3, 2, 1])
+    }
+
+    #[test]
+    fn test_resolver_account_at_serialization() {
+        let account_at: Resolver<InstructionGroups> =
+            Resolver::AccountAt(Pubkey::new_from_array([9; 32]));
+        let mut buffer: Vec<u8> = Vec::new();
+        account_at.serialize(&mut buffer).unwrap();
+        let mut expected = vec![5];
+        expected.extend_from_slice(&[9; 32]);
+        assert_eq!(buffer, expected)
+    }
+
+    #[test]
+    fn test_serializable_instruction_round_trip() {
+        let instruction = Instruction {
+            program_id: Pubkey::new_from_array([1; 32]),
+            accounts: vec![
+                AccountMeta::new(RESOLVER_PUBKEY_PAYER, true),
+                AccountMeta::new_readonly(Pubkey::new_from_array([2; 32]), false),
+            ],
+            data: vec![1, 2, 3],
+        };
+        let serializable = SerializableInstruction::from(instruction.clone());
+        assert_eq!(Instruction::from(serializable.clone()), instruction);
+        assert_eq!(
+            AccountMeta::from(serializable.accounts[0].clone()),
+            instruction.accounts[0]
+        );
+
+        // results can be compared and used as map keys
+        let resolved = Resolver::Resolved(InstructionGroups(vec![InstructionGroup {
+            instructions: vec![serializable],
+            address_lookup_tables: vec![],
+        }]));
+        let missing = missing_account(RESOLVER_PUBKEY_PAYER);
+        assert_ne!(resolved, missing);
+        let results = std::collections::HashSet::from([resolved.clone(), missing, resolved]);
+        assert_eq!(results.len(), 2);
+    }
+
+    #[test]
+    fn test_resolver_unresolvable_serialization() {
+        let resolver = unresolvable(UNRESOLVABLE_PAUSED, "paused");
+        let mut buffer: Vec<u8> = Vec::new();
+        resolver.serialize(&mut buffer).unwrap();
+        assert_eq!(
+            buffer,
+            [6, 3, 0, 0, 0, 6, 0, 0, 0, b'p', b'a', b'u', b's', b'e', b'd']
+        );
+    }
+
+    #[test]
+    fn test_unresolvable_truncates_reason() {
+        let reason = format!("a{}", "é".repeat(MAX_UNRESOLVABLE_REASON_LEN));
+        let Resolver::Unresolvable { code, reason } = unresolvable(UNRESOLVABLE_CUSTOM, &reason)
+        else {
+            panic!("expected unresolvable");
+        };
+        assert_eq!(code, UNRESOLVABLE_CUSTOM);
+        // truncated at a character boundary
+        assert_eq!(
+            reason,
+            format!("a{}", "é".repeat(MAX_UNRESOLVABLE_REASON_LEN / 2 - 1))
+        );
+        assert_eq!(
+            unresolvable(UNRESOLVABLE_CUSTOM, "short"),
+            Resolver::Unresolvable {
+                code: UNRESOLVABLE_CUSTOM,
+                reason: "short".to_string()
+            }
+        );
+    }
 }
@@ -0,0 +1,166 @@
+//! Collection of every missing account and lookup table of a resolution pass into a single `Resolver::Missing`.
+
+use anchor_lang::prelude::*;
+
+use crate::{find_account, InstructionGroups, MissingAccounts, Resolver};
+
+/// Collects the accounts and lookup tables a resolver needs but was not given, so that a single
+/// `Resolver::Missing` can request all of them instead of one per simulation.
+///
+/// Keys are deduplicated and kept in the order they were first added.
+///
+/// Usage:
+///
+/// ```rust
+/// use anchor_lang::prelude::*;
+/// use executor_account_resolver_svm::{
+///     InstructionGroups, MissingCollector, Resolver, RESOLVER_EXECUTE_VAA_V1,
+/// };
+///
+/// #[derive(Accounts)]
+/// pub struct Resolve {}
+///
+/// #[instruction(discriminator = &RESOLVER_EXECUTE_VAA_V1)]
+/// pub fn resolve_execute_vaa_v1(ctx: Context<Resolve>, vaa_body: Vec<u8>) -> Result<Resolver<InstructionGroups>> {
+///     let mut missing = MissingCollector::new();
+///     // neither account depends on the other, so both are requested in the same pass
+///     let config = missing.find(ctx.remaining_accounts, pubkey!("SysvarC1ock11111111111111111111111111111111"));
+///     let mint = missing.find(ctx.remaining_accounts, pubkey!("So11111111111111111111111111111111111111112"));
+///     missing.add_lookup_table(pubkey!("AddressLookupTab1e1111111111111111111111111"));
+///     let (Some(config), Some(mint)) = (config, mint) else {
+///         return Ok(missing.into_resolver().unwrap());
+///     };
+///     Ok(Resolver::Resolved(InstructionGroups(vec![
+///         // build your `InstructionGroup`s here
+///     ])))
+/// }
+/// ```
+#[derive(Clone, Default)]
+pub struct MissingCollector {
+    accounts: Vec<Pubkey>,
+    address_lookup_tables: Vec<Pubkey>,
+}
+
+impl MissingCollector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Finds `pubkey` in `remaining_accounts`, adding it to the missing accounts if it is absent.
+    pub fn find<'c, 'info>(
+        &mut self,
+        remaining_accounts: &'c [AccountInfo<'info>],
+        pubkey: Pubkey,
+    ) -> Option<&'c AccountInfo<'info>> {
+        let account = find_account(remaining_accounts, pubkey);
+        if account.is_none() {
+            self.add_account(pubkey);
+        }
+        account
+    }
+
+    /// Adds an account which is needed by a later pass, e.g. one which the resolver can derive
+    /// but does not read yet.
+    pub fn add_account(&mut self, pubkey: Pubkey) {
+        if !self.accounts.contains(&pubkey) {
+            self.accounts.push(pubkey);
+        }
+    }
+
+    /// Adds an address lookup table for the client to use when compiling the next pass.
+    pub fn add_lookup_table(&mut self, pubkey: Pubkey) {
+        if !self.address_lookup_tables.contains(&pubkey) {
+            self.address_lookup_tables.push(pubkey);
+        }
+    }
+
+    pub fn accounts(&self) -> &[Pubkey] {
+        &self.accounts
+    }
+
+    pub fn address_lookup_tables(&self) -> &[Pubkey] {
+        &self.address_lookup_tables
+    }
+
+    /// Whether no accounts or lookup tables have been added.
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty() && self.address_lookup_tables.is_empty()
+    }
+
+    /// `Resolver::Missing` with everything collected, or `None` if nothing is missing.
+    pub fn into_resolver(self) -> Option<Resolver<InstructionGroups>> {
+        if self.is_empty() {
+            return None;
+        }
+        Some(Resolver::Missing(self.into()))
+    }
+}
+
+impl Extend<Pubkey> for MissingCollector {
+    /// Adds each account, as with `add_account`.
+    fn extend<I: IntoIterator<Item = Pubkey>>(&mut self, iter: I) {
+        for pubkey in iter {
+            self.add_account(pubkey);
+        }
+    }
+}
+
+impl From<MissingCollector> for MissingAccounts {
+    fn from(collector: MissingCollector) -> Self {
+        MissingAccounts {
+            accounts: collector.accounts,
+            address_lookup_tables: collector.address_lookup_tables,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_missing_collector() {
+        let keys: Vec<Pubkey> = (0..4).map(|n| Pubkey::new_from_array([n; 32])).collect();
+        let mut lamports = 0;
+        let mut data = vec![];
+        let supplied = [AccountInfo::new(
+            &keys[0],
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &keys[0],
+            false,
+            0,
+        )];
+
+        let mut missing = MissingCollector::new();
+        assert!(missing.is_empty());
+        assert!(missing.clone().into_resolver().is_none());
+        assert_eq!(missing.find(&supplied, keys[0]).unwrap().key, &keys[0]);
+        assert!(missing.is_empty());
+        assert!(missing.find(&supplied, keys[2]).is_none());
+        assert!(missing.find(&supplied, keys[1]).is_none());
+        missing.add_account(keys[2]);
+        missing.extend([keys[3], keys[1]]);
+        missing.add_lookup_table(keys[3]);
+        missing.add_lookup_table(keys[3]);
+        assert_eq!(missing.accounts(), [keys[2], keys[1], keys[3]]);
+        assert_eq!(missing.address_lookup_tables(), [keys[3]]);
+
+        let Some(Resolver::Missing(accounts)) = missing.into_resolver() else {
+            panic!("expected missing accounts");
+        };
+        assert_eq!(accounts.accounts, [keys[2], keys[1], keys[3]]);
+        assert_eq!(accounts.address_lookup_tables, [keys[3]]);
+
+        // lookup tables alone are still requested
+        let mut missing = MissingCollector::new();
+        missing.add_lookup_table(keys[0]);
+        assert!(!missing.is_empty());
+        assert!(matches!(
+            missing.into_resolver(),
+            Some(Resolver::Missing(MissingAccounts { accounts, .. })) if accounts.is_empty()
+        ));
+    }
+}
@@ -0,0 +1,233 @@
+//! A result type through which missing accounts short-circuit resolution with `?`.
+
+use anchor_lang::prelude::*;
+
+use crate::{find_account, MissingAccounts, MissingCollector, Resolver};
+
+/// Why a resolution pass stopped early: accounts are missing or an error occurred.
+#[derive(Debug)]
+pub enum ResolverError {
+    /// The pass needs these accounts, to be returned as `Resolver::Missing`.
+    Missing(MissingAccounts),
+    /// The pass failed, e.g. an account could not be deserialized.
+    Error(anchor_lang::error::Error),
+}
+
+/// The result of a resolution pass written with `require_account` and `?`.
+/// Convert it to the instruction's return value with `resolve_missing`.
+pub type ResolveResult<T> = std::result::Result<T, ResolverError>;
+
+impl From<anchor_lang::error::Error> for ResolverError {
+    fn from(err: anchor_lang::error::Error) -> Self {
+        ResolverError::Error(err)
+    }
+}
+
+impl From<ProgramError> for ResolverError {
+    fn from(err: ProgramError) -> Self {
+        ResolverError::Error(err.into())
+    }
+}
+
+impl From<MissingAccounts> for ResolverError {
+    fn from(missing: MissingAccounts) -> Self {
+        ResolverError::Missing(missing)
+    }
+}
+
+impl From<MissingCollector> for ResolverError {
+    fn from(missing: MissingCollector) -> Self {
+        ResolverError::Missing(missing.into())
+    }
+}
+
+/// Finds `pubkey` in `remaining_accounts`, or stops the pass to request it.
+///
+/// Usage:
+///
+/// ```rust
+/// use anchor_lang::prelude::*;
+/// use executor_account_resolver_svm::{
+///     require_account, require_account_data, resolve_missing, InstructionGroups, ResolveResult,
+///     Resolver, RESOLVER_EXECUTE_VAA_V1,
+/// };
+///
+/// #[derive(Accounts)]
+/// pub struct Resolve {}
+///
+/// #[instruction(discriminator = &RESOLVER_EXECUTE_VAA_V1)]
+/// pub fn resolve_execute_vaa_v1(ctx: Context<Resolve>, vaa_body: Vec<u8>) -> Result<Resolver<InstructionGroups>> {
+///     resolve_missing(resolve(ctx.remaining_accounts))
+/// }
+///
+/// fn resolve(remaining_accounts: &[AccountInfo]) -> ResolveResult<Resolver<InstructionGroups>> {
+///     let config = require_account(remaining_accounts, pubkey!("SysvarC1ock11111111111111111111111111111111"))?;
+///     // e.g. an `#[account]` of the resolver program
+///     let count: Counter = require_account_data(remaining_accounts, pubkey!("So11111111111111111111111111111111111111112"))?;
+///     Ok(Resolver::Resolved(InstructionGroups(vec![
+///         // build your `InstructionGroup`s here
+///     ])))
+/// }
+/// # #[derive(Clone)]
+/// # struct Counter;
+/// # impl AccountDeserialize for Counter {
+/// #     fn try_deserialize_unchecked(_buf: &mut &[u8]) -> Result<Self> { Ok(Counter) }
+/// # }
+/// # impl Owner for Counter {
+/// #     fn owner() -> Pubkey { Pubkey::default() }
+/// # }
+/// ```
+pub fn require_account<'c, 'info>(
+    remaining_accounts: &'c [AccountInfo<'info>],
+    pubkey: Pubkey,
+) -> ResolveResult<&'c AccountInfo<'info>> {
+    find_account(remaining_accounts, pubkey).ok_or_else(|| {
+        ResolverError::Missing(MissingAccounts {
+            accounts: vec![pubkey],
+            address_lookup_tables: vec![],
+        })
+    })
+}
+
+/// Finds `pubkey` in `remaining_accounts` and deserializes it as `T`, or stops the pass to request it.
+/// An account not owned by `T`'s program and deserialization failures, including a discriminator mismatch,
+/// stop the pass with the Anchor error, as with `Account::try_from`.
+pub fn require_account_data<T: AccountDeserialize + Owner>(
+    remaining_accounts: &[AccountInfo],
+    pubkey: Pubkey,
+) -> ResolveResult<T> {
+    let account = require_account(remaining_accounts, pubkey)?;
+    if account.owner != &T::owner() {
+        return Err(
+            anchor_lang::error::Error::from(ErrorCode::AccountOwnedByWrongProgram)
+                .with_pubkeys((*account.owner, T::owner()))
+                .into(),
+        );
+    }
+    let data = account.try_borrow_data()?;
+    Ok(T::try_deserialize(&mut &data[..])?)
+}
+
+/// Converts the result of a resolution pass into the instruction's return value,
+/// returning `Resolver::Missing` for missing accounts.
+pub fn resolve_missing<T>(result: ResolveResult<Resolver<T>>) -> Result<Resolver<T>> {
+    match result {
+        Ok(resolver) => Ok(resolver),
+        Err(ResolverError::Missing(missing)) => Ok(Resolver::Missing(missing)),
+        Err(ResolverError::Error(err)) => Err(err),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::InstructionGroups;
+
+    #[derive(Clone)]
+    struct Counter(u8);
+
+    impl AccountSerialize for Counter {}
+
+    impl AccountDeserialize for Counter {
+        fn try_deserialize_unchecked(buf: &mut &[u8]) -> Result<Self> {
+            match buf {
+                [count] => Ok(Counter(*count)),
+                _ => Err(ErrorCode::AccountDidNotDeserialize.into()),
+            }
+        }
+    }
+
+    impl Owner for Counter {
+        fn owner() -> Pubkey {
+            Pubkey::new_from_array([1; 32])
+        }
+    }
+
+    fn resolve(remaining_accounts: &[AccountInfo]) -> ResolveResult<Resolver<InstructionGroups>> {
+        let first: Counter =
+            require_account_data(remaining_accounts, Pubkey::new_from_array([1; 32]))?;
+        require_account(remaining_accounts, Pubkey::new_from_array([first.0; 32]))?;
+        Ok(Resolver::Resolved(InstructionGroups(vec![])))
+    }
+
+    #[test]
+    fn test_resolve_missing() {
+        let keys = [
+            Pubkey::new_from_array([1; 32]),
+            Pubkey::new_from_array([2; 32]),
+        ];
+        let mut lamports = [0, 0];
+        let mut data = [vec![2], vec![]];
+        let [first_lamports, second_lamports] = &mut lamports;
+        let [first_data, second_data] = &mut data;
+        let infos = [
+            AccountInfo::new(
+                &keys[0],
+                false,
+                false,
+                first_lamports,
+                first_data,
+                &keys[0],
+                false,
+                0,
+            ),
+            AccountInfo::new(
+                &keys[1],
+                false,
+                false,
+                second_lamports,
+                second_data,
+                &keys[0],
+                false,
+                0,
+            ),
+        ];
+
+        let missing = |result| match resolve_missing(result) {
+            Ok(Resolver::Missing(missing)) => missing.accounts,
+            _ => panic!("expected missing accounts"),
+        };
+        assert_eq!(missing(resolve(&[])), [keys[0]]);
+        assert_eq!(missing(resolve(&infos[..1])), [keys[1]]);
+        assert!(matches!(
+            resolve_missing(resolve(&infos)),
+            Ok(Resolver::Resolved(_))
+        ));
+
+        // deserialization failures are errors rather than missing accounts
+        let mut long = vec![1, 2];
+        let mut long_lamports = 0;
+        let invalid = [AccountInfo::new(
+            &keys[0],
+            false,
+            false,
+            &mut long_lamports,
+            &mut long,
+            &keys[0],
+            false,
+            0,
+        )];
+        assert_eq!(
+            resolve_missing(resolve(&invalid)).err().unwrap(),
+            ErrorCode::AccountDidNotDeserialize.into()
+        );
+
+        // as are accounts owned by another program, even with valid data
+        let mut spoofed_lamports = 0;
+        let mut spoofed_data = vec![2];
+        let spoofed = [AccountInfo::new(
+            &keys[0],
+            false,
+            false,
+            &mut spoofed_lamports,
+            &mut spoofed_data,
+            &keys[1],
+            false,
+            0,
+        )];
+        assert_eq!(
+            resolve_missing(resolve(&spoofed)).err().unwrap(),
+            ErrorCode::AccountOwnedByWrongProgram.into()
+        );
+    }
+}
@@ -0,0 +1,1034 @@
+//! Writing a `Resolver` to a result account for `Resolver::Account()` and `Resolver::AccountAt` returns.
+
+use std::{fmt, io};
+
+use anchor_lang::{
+    prelude::*,
+    solana_program::{
+        entrypoint::MAX_PERMITTED_DATA_INCREASE, program::MAX_RETURN_DATA,
+        system_instruction::MAX_PERMITTED_DATA_LENGTH,
+    },
+    system_program,
+};
+
+use crate::{
+    MissingCollector, Resolver, RESOLVER_PUBKEY_PAYER, RESOLVER_RESULT_ACCOUNT,
+    RESOLVER_RESULT_ACCOUNT_SEED,
+};
+
+/// The canonical result account of `program_id`.
+pub fn result_account_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[RESOLVER_RESULT_ACCOUNT_SEED], program_id).0
+}
+
+/// The seeds, without the bump, of the per-request result account for `request_seed`, e.g. `VaaBody::digest`.
+///
+/// Unlike the canonical result account, which is shared by every caller, a per-request result account
+/// is only written by resolutions of the same request, so concurrent relayers cannot observe each other's results.
+pub fn request_result_account_seeds(request_seed: &[u8; 32]) -> [&[u8]; 2] {
+    [RESOLVER_RESULT_ACCOUNT_SEED, request_seed]
+}
+
+/// The per-request result account of `program_id` for `request_seed`, and its bump.
+pub fn request_result_account_address(
+    program_id: &Pubkey,
+    request_seed: &[u8; 32],
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&request_result_account_seeds(request_seed), program_id)
+}
+
+/// The prefix of the marker pubkey through which the client passes the offset of the next chunk of a
+/// `Resolver::Partial` result. The prefix is followed by the offset as a little-endian `u32` and zeros.
+pub const RESOLVER_RESULT_OFFSET_PREFIX: &[u8; 14] = b"result_offset_";
+
+/// The marker pubkey passed as the last remaining account to request the chunk starting at `offset`.
+pub fn result_offset_marker(offset: u32) -> Pubkey {
+    let mut bytes = [0; 32];
+    bytes[..RESOLVER_RESULT_OFFSET_PREFIX.len()].copy_from_slice(RESOLVER_RESULT_OFFSET_PREFIX);
+    bytes[RESOLVER_RESULT_OFFSET_PREFIX.len()..RESOLVER_RESULT_OFFSET_PREFIX.len() + 4]
+        .copy_from_slice(&offset.to_le_bytes());
+    Pubkey::new_from_array(bytes)
+}
+
+/// The offset of the chunk of a paginated result requested by the client, or 0 if none was requested.
+///
+/// Usage: `ResultAccountWriter::new(..).write_paginated(&result, result_offset(ctx.remaining_accounts))`
+pub fn result_offset(remaining_accounts: &[AccountInfo]) -> u32 {
+    remaining_accounts
+        .last()
+        .and_then(|account| parse_result_offset_marker(account.key))
+        .unwrap_or(0)
+}
+
+fn parse_result_offset_marker(pubkey: &Pubkey) -> Option<u32> {
+    let bytes = pubkey.as_ref();
+    let (prefix, rest) = bytes.split_at(RESOLVER_RESULT_OFFSET_PREFIX.len());
+    let (offset, padding) = rest.split_at(4);
+    (prefix == RESOLVER_RESULT_OFFSET_PREFIX && padding.iter().all(|b| *b == 0))
+        .then(|| u32::from_le_bytes(offset.try_into().unwrap()))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResultAccountError {
+    /// The result account was not passed as writable.
+    NotWritable,
+    /// The result account is not owned by the resolver program.
+    InvalidOwner,
+    /// The result account is not the expected PDA of the resolver program.
+    InvalidAddress,
+    /// The result account does not start with the `RESOLVER_RESULT_ACCOUNT` discriminator.
+    InvalidDiscriminator,
+    /// The result needs more space than can be allocated in a single transaction.
+    TooLarge { size: usize, max: usize },
+    /// The requested offset is beyond the end of the serialized result.
+    InvalidOffset { offset: u32, len: usize },
+}
+
+impl fmt::Display for ResultAccountError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResultAccountError::NotWritable => write!(f, "result account is not writable"),
+            ResultAccountError::InvalidOwner => {
+                write!(f, "result account is not owned by the resolver program")
+            }
+            ResultAccountError::InvalidAddress => {
+                write!(f, "result account is not the expected PDA")
+            }
+            ResultAccountError::InvalidDiscriminator => {
+                write!(f, "result account has an invalid discriminator")
+            }
+            ResultAccountError::TooLarge { size, max } => write!(
+                f,
+                "result account must be {size} bytes but can be at most {max} bytes"
+            ),
+            ResultAccountError::InvalidOffset { offset, len } => write!(
+                f,
+                "result offset {offset} is beyond the end of the {len} byte result"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ResultAccountError {}
+
+impl From<ResultAccountError> for anchor_lang::error::Error {
+    fn from(err: ResultAccountError) -> Self {
+        msg!("{}", err);
+        match err {
+            ResultAccountError::NotWritable => ErrorCode::ConstraintMut.into(),
+            ResultAccountError::InvalidOwner => ErrorCode::AccountOwnedByWrongProgram.into(),
+            ResultAccountError::InvalidAddress => ErrorCode::ConstraintSeeds.into(),
+            ResultAccountError::InvalidDiscriminator => {
+                ErrorCode::AccountDiscriminatorMismatch.into()
+            }
+            ResultAccountError::TooLarge { .. } => ProgramError::InvalidRealloc.into(),
+            ResultAccountError::InvalidOffset { .. } => ProgramError::InvalidArgument.into(),
+        }
+    }
+}
+
+/// Writes a result to the canonical result account, growing it and funding its rent from the payer as needed.
+///
+/// The result account must be the canonical result account of the resolver program, already initialized
+/// with the `RESOLVER_RESULT_ACCOUNT` discriminator, e.g. with `space = 8 + RESOLVER_RESULT_ACCOUNT_INIT_SIZE`. It is grown to exactly the size
+/// of the serialized result and is never shrunk. As an account can only grow by `MAX_PERMITTED_DATA_INCREASE`
+/// per transaction, larger results require the account to be pre-allocated or `write_paginated`.
+///
+/// Usage:
+///
+/// ```rust
+/// use anchor_lang::prelude::*;
+/// use executor_account_resolver_svm::{InstructionGroups, Resolver, ResultAccountWriter};
+///
+/// pub fn resolve_execute_vaa_v1<'info>(
+///     result: &AccountInfo<'info>,
+///     payer: &AccountInfo<'info>,
+///     system_program: &AccountInfo<'info>,
+///     groups: InstructionGroups,
+/// ) -> Result<Resolver<InstructionGroups>> {
+///     ResultAccountWriter::new(&crate_id(), result, payer, system_program)
+///         .write(&Resolver::Resolved(groups))
+/// }
+/// # fn crate_id() -> Pubkey { Pubkey::default() }
+/// ```
+pub struct ResultAccountWriter<'a, 'info> {
+    program_id: &'a Pubkey,
+    result: &'a AccountInfo<'info>,
+    payer: &'a AccountInfo<'info>,
+    system_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> ResultAccountWriter<'a, 'info> {
+    /// `program_id` is the resolver program and `payer` is the account passed for `RESOLVER_PUBKEY_PAYER`.
+    pub fn new(
+        program_id: &'a Pubkey,
+        result: &'a AccountInfo<'info>,
+        payer: &'a AccountInfo<'info>,
+        system_program: &'a AccountInfo<'info>,
+    ) -> Self {
+        ResultAccountWriter {
+            program_id,
+            result,
+            payer,
+            system_program,
+        }
+    }
+
+    /// Writes `resolver` after the `RESOLVER_RESULT_ACCOUNT` discriminator and returns `Resolver::Account()`.
+    pub fn write<T: AnchorSerialize, R>(&self, resolver: &Resolver<T>) -> Result<Resolver<R>> {
+        self.write_to(resolver, &result_account_address(self.program_id))?;
+        Ok(Resolver::Account())
+    }
+
+    fn write_to<T: AnchorSerialize>(&self, resolver: &Resolver<T>, address: &Pubkey) -> Result<()> {
+        self.reserve_at(address, resolver.serialized_len())?;
+
+        let mut data = self.result.try_borrow_mut_data()?;
+        resolver.serialize(&mut &mut data[RESOLVER_RESULT_ACCOUNT.len()..])?;
+        Ok(())
+    }
+
+    /// Writes `resolver` to a per-request result account and returns `Resolver::AccountAt` its address.
+    ///
+    /// If the account does not exist yet, it is created for the resolver program with the signer `seeds`,
+    /// i.e. `request_result_account_seeds` followed by the bump, and funded by the payer.
+    /// Only the rent the account lacks is transferred, so creation succeeds even if the address was funded beforehand.
+    ///
+    /// Usage:
+    ///
+    /// ```rust
+    /// use anchor_lang::prelude::*;
+    /// use executor_account_resolver_svm::{
+    ///     request_result_account_seeds, InstructionGroups, Resolver, ResultAccountWriter, VaaBody,
+    /// };
+    ///
+    /// pub fn resolve_execute_vaa_v1<'info>(
+    ///     result: &AccountInfo<'info>,
+    ///     payer: &AccountInfo<'info>,
+    ///     system_program: &AccountInfo<'info>,
+    ///     vaa: VaaBody,
+    ///     groups: InstructionGroups,
+    /// ) -> Result<Resolver<InstructionGroups>> {
+    ///     let digest = vaa.digest();
+    ///     let [prefix, request_seed] = request_result_account_seeds(&digest);
+    ///     let (_, bump) = Pubkey::find_program_address(&[prefix, request_seed], &crate_id());
+    ///     ResultAccountWriter::new(&crate_id(), result, payer, system_program)
+    ///         .write_at(&Resolver::Resolved(groups), &[prefix, request_seed, &[bump]])
+    /// }
+    /// # fn crate_id() -> Pubkey { Pubkey::default() }
+    /// ```
+    pub fn write_at<T: AnchorSerialize, R>(
+        &self,
+        resolver: &Resolver<T>,
+        seeds: &[&[u8]],
+    ) -> Result<Resolver<R>> {
+        let address = Pubkey::create_program_address(seeds, self.program_id)
+            .map_err(|_| ResultAccountError::InvalidAddress)?;
+        if self.result.owner == &system_program::ID && self.result.data_is_empty() {
+            if self.result.key != &address {
+                return Err(ResultAccountError::InvalidAddress.into());
+            }
+            // as in Anchor's `init_if_needed`, since `create_account` fails if anyone funded the address first
+            let space = RESOLVER_RESULT_ACCOUNT.len() + resolver.serialized_len();
+            self.fund(space)?;
+            system_program::allocate(
+                CpiContext::new_with_signer(
+                    self.system_program.clone(),
+                    system_program::Allocate {
+                        account_to_allocate: self.result.clone(),
+                    },
+                    &[seeds],
+                ),
+                space as u64,
+            )?;
+            system_program::assign(
+                CpiContext::new_with_signer(
+                    self.system_program.clone(),
+                    system_program::Assign {
+                        account_to_assign: self.result.clone(),
+                    },
+                    &[seeds],
+                ),
+                self.program_id,
+            )?;
+            self.result.try_borrow_mut_data()?[..RESOLVER_RESULT_ACCOUNT.len()]
+                .copy_from_slice(RESOLVER_RESULT_ACCOUNT);
+        }
+        self.write_to(resolver, &address)?;
+        Ok(Resolver::AccountAt(address))
+    }
+
+    /// Writes the chunk of the serialized `resolver` starting at `offset` to the start of the result account,
+    /// where `offset` is `result_offset(remaining_accounts)`.
+    ///
+    /// If the rest of the result does not fit within the size the account can grow to in this transaction,
+    /// as much as fits is written and `Resolver::Partial` is returned with the offset of the next chunk.
+    /// Otherwise the final chunk is written and `Resolver::Account()` is returned.
+    /// Each chunk overwrites the previous one, so the client must collect them after each simulation.
+    pub fn write_paginated<T: AnchorSerialize, R>(
+        &self,
+        resolver: &Resolver<T>,
+        offset: u32,
+    ) -> Result<Resolver<R>> {
+        self.check(&result_account_address(self.program_id))?;
+        let len = resolver.serialized_len();
+        let start = offset as usize;
+        if start > len || (start == len && len > 0) {
+            return Err(ResultAccountError::InvalidOffset { offset, len }.into());
+        }
+        let capacity = self.max_size() - RESOLVER_RESULT_ACCOUNT.len();
+        let chunk = usize::min(len - start, capacity);
+        self.grow(RESOLVER_RESULT_ACCOUNT.len() + chunk)?;
+
+        let mut data = self.result.try_borrow_mut_data()?;
+        let (out, rest) = data[RESOLVER_RESULT_ACCOUNT.len()..].split_at_mut(chunk);
+        resolver.serialize(&mut Window { skip: start, out })?;
+        rest.fill(0);
+        if start + chunk == len {
+            Ok(Resolver::Account())
+        } else {
+            Ok(Resolver::Partial {
+                next_offset: (start + chunk) as u32,
+            })
+        }
+    }
+
+    /// Checks the result account, grows it to hold at least `len` bytes after the discriminator
+    /// and zeroes any previous result, e.g. before streaming a result into it with `InstructionGroupsWriter`.
+    pub fn reserve(&self, len: usize) -> Result<()> {
+        self.reserve_at(&result_account_address(self.program_id), len)
+    }
+
+    fn reserve_at(&self, address: &Pubkey, len: usize) -> Result<()> {
+        self.check(address)?;
+        self.grow(RESOLVER_RESULT_ACCOUNT.len() + len)?;
+        // so that clients can decode with `DecodeLimits::reject_trailing_data`
+        self.result.try_borrow_mut_data()?[RESOLVER_RESULT_ACCOUNT.len()..].fill(0);
+        Ok(())
+    }
+
+    /// Checks that the result account is the writable result account at `address`, as `Account::try_from` would.
+    fn check(&self, address: &Pubkey) -> Result<()> {
+        if !self.result.is_writable {
+            return Err(ResultAccountError::NotWritable.into());
+        }
+        if self.result.owner != self.program_id {
+            return Err(ResultAccountError::InvalidOwner.into());
+        }
+        if self.result.key != address {
+            return Err(ResultAccountError::InvalidAddress.into());
+        }
+        // guards against overwriting another of the program's accounts
+        if !self
+            .result
+            .try_borrow_data()?
+            .starts_with(RESOLVER_RESULT_ACCOUNT)
+        {
+            return Err(ResultAccountError::InvalidDiscriminator.into());
+        }
+        Ok(())
+    }
+
+    /// The largest size the result account can grow to in this transaction.
+    fn max_size(&self) -> usize {
+        let current = self.result.data_len();
+        usize::max(
+            current,
+            usize::min(
+                current + MAX_PERMITTED_DATA_INCREASE,
+                MAX_PERMITTED_DATA_LENGTH as usize,
+            ),
+        )
+    }
+
+    /// Grows the result account to at least `size` bytes.
+    fn grow(&self, size: usize) -> Result<()> {
+        if size <= self.result.data_len() {
+            return Ok(());
+        }
+        let max = self.max_size();
+        if size > max {
+            return Err(ResultAccountError::TooLarge { size, max }.into());
+        }
+        self.fund(size)?;
+        self.result.realloc(size, false)?;
+        Ok(())
+    }
+
+    /// Transfers the lamports the result account lacks to be rent exempt at `size` bytes from the payer.
+    fn fund(&self, size: usize) -> Result<()> {
+        let lamports = Rent::get()?
+            .minimum_balance(size)
+            .saturating_sub(self.result.lamports());
+        if lamports > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    self.system_program.clone(),
+                    system_program::Transfer {
+                        from: self.payer.clone(),
+                        to: self.result.clone(),
+                    },
+                ),
+                lamports,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns `resolver` as return data if it fits in `MAX_RETURN_DATA`, and otherwise writes it to the canonical
+/// result account of `program_id` and returns `Resolver::Account()`.
+///
+/// Writing needs the result account, the payer and the system program. Those missing from `remaining_accounts`
+/// are requested with `Resolver::Missing`, so the resolver must accept them appended to its other accounts.
+/// The payer is requested as `RESOLVER_PUBKEY_PAYER` and found as the signer, since the client passes the fee payer.
+/// The result account must already be initialized, see `ResultAccountWriter`.
+///
+/// Usage:
+///
+/// ```rust
+/// use anchor_lang::prelude::*;
+/// use executor_account_resolver_svm::{
+///     return_or_write, InstructionGroups, Resolver, RESOLVER_EXECUTE_VAA_V1,
+/// };
+///
+/// #[derive(Accounts)]
+/// pub struct Resolve {}
+///
+/// #[instruction(discriminator = &RESOLVER_EXECUTE_VAA_V1)]
+/// pub fn resolve_execute_vaa_v1(ctx: Context<Resolve>, vaa_body: Vec<u8>) -> Result<Resolver<InstructionGroups>> {
+///     let groups = InstructionGroups(vec![
+///         // build your `InstructionGroup`s here
+///     ]);
+///     return_or_write(Resolver::Resolved(groups), &crate_id(), ctx.remaining_accounts)
+/// }
+/// # fn crate_id() -> Pubkey { Pubkey::default() }
+/// ```
+pub fn return_or_write<T: AnchorSerialize>(
+    resolver: Resolver<T>,
+    program_id: &Pubkey,
+    remaining_accounts: &[AccountInfo],
+) -> Result<Resolver<T>> {
+    if resolver.serialized_len() <= MAX_RETURN_DATA {
+        return Ok(resolver);
+    }
+    let mut missing = MissingCollector::new();
+    let result = missing.find(remaining_accounts, result_account_address(program_id));
+    let payer = remaining_accounts.iter().find(|account| account.is_signer);
+    if payer.is_none() {
+        missing.add_account(RESOLVER_PUBKEY_PAYER);
+    }
+    let system_program = missing.find(remaining_accounts, system_program::ID);
+    let (Some(result), Some(payer), Some(system_program)) = (result, payer, system_program) else {
+        return Ok(Resolver::Missing(missing.into()));
+    };
+    ResultAccountWriter::new(program_id, result, payer, system_program).write(&resolver)
+}
+
+/// Counts the bytes written to it, to size the result account before serializing into it.
+pub(crate) struct LenCounter(pub(crate) usize);
+
+impl io::Write for LenCounter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Copies the bytes written to it after skipping the first `skip`, until `out` is full.
+struct Window<'b> {
+    skip: usize,
+    out: &'b mut [u8],
+}
+
+impl io::Write for Window<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let skipped = usize::min(self.skip, buf.len());
+        self.skip -= skipped;
+        let rest = &buf[skipped..];
+        let n = usize::min(rest.len(), self.out.len());
+        let (head, tail) = std::mem::take(&mut self.out).split_at_mut(n);
+        head.copy_from_slice(&rest[..n]);
+        self.out = tail;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Once;
+
+    use anchor_lang::solana_program::{
+        entrypoint::{ProgramResult, SUCCESS},
+        instruction::Instruction,
+        program_stubs::{set_syscall_stubs, SyscallStubs},
+    };
+
+    use super::*;
+    use crate::{InstructionGroup, InstructionGroups, MissingAccounts};
+
+    const OWNER: Pubkey = Pubkey::new_from_array([5; 32]);
+
+    /// Emulates the rent sysvar and the system program instructions used by the writer,
+    /// which are otherwise unavailable off-chain.
+    struct SystemProgramStubs;
+
+    impl SyscallStubs for SystemProgramStubs {
+        fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+            unsafe { *(var_addr as *mut Rent) = Rent::default() };
+            SUCCESS
+        }
+
+        fn sol_invoke_signed(
+            &self,
+            instruction: &Instruction,
+            account_infos: &[AccountInfo],
+            signers_seeds: &[&[&[u8]]],
+        ) -> ProgramResult {
+            assert_eq!(instruction.program_id, system_program::ID);
+            let accounts: Vec<&AccountInfo> = instruction
+                .accounts
+                .iter()
+                .map(|meta| {
+                    let account = account_infos
+                        .iter()
+                        .find(|account| account.key == &meta.pubkey)
+                        .unwrap();
+                    let signed = signers_seeds.iter().any(|seeds| {
+                        Pubkey::create_program_address(seeds, &OWNER).as_ref() == Ok(account.key)
+                    });
+                    assert!(!meta.is_signer || account.is_signer || signed);
+                    account
+                })
+                .collect();
+            let (tag, args) = instruction.data.split_at(4);
+            let u64_at =
+                |offset: usize| u64::from_le_bytes(args[offset..offset + 8].try_into().unwrap());
+            match u32::from_le_bytes(tag.try_into().unwrap()) {
+                // `SystemInstruction::Transfer`
+                2 => {
+                    let lamports = u64_at(0);
+                    let mut from = accounts[0].try_borrow_mut_lamports()?;
+                    **from = from
+                        .checked_sub(lamports)
+                        .ok_or(ProgramError::InsufficientFunds)?;
+                    **accounts[1].try_borrow_mut_lamports()? += lamports;
+                    Ok(())
+                }
+                // `SystemInstruction::Allocate`
+                8 => {
+                    assert_eq!(accounts[0].owner, &system_program::ID);
+                    assert!(accounts[0].data_is_empty());
+                    accounts[0].realloc(u64_at(0) as usize, true)
+                }
+                // `SystemInstruction::Assign`
+                1 => {
+                    assert_eq!(accounts[0].owner, &system_program::ID);
+                    accounts[0].assign(&Pubkey::new_from_array(args[..32].try_into().unwrap()));
+                    Ok(())
+                }
+                tag => panic!("unexpected system instruction {tag}"),
+            }
+        }
+    }
+
+    fn set_system_program_stubs() {
+        static STUBS: Once = Once::new();
+        STUBS.call_once(|| {
+            set_syscall_stubs(Box::new(SystemProgramStubs));
+        });
+    }
+
+    /// The original data length precedes the key, where `AccountInfo::original_data_len` reads it.
+    #[repr(C)]
+    struct SerializedHeader {
+        padding: u32,
+        original_data_len: u32,
+        key: Pubkey,
+    }
+
+    /// An account laid out as in the serialized program input, which `AccountInfo::realloc` relies on
+    /// to grow the data in place.
+    struct SerializedAccount {
+        header: Box<SerializedHeader>,
+        /// The data length, then the data followed by room for `MAX_PERMITTED_DATA_INCREASE` bytes.
+        buffer: Vec<u64>,
+        lamports: u64,
+        owner: Pubkey,
+    }
+
+    impl SerializedAccount {
+        fn new(key: Pubkey, owner: Pubkey, lamports: u64, data: &[u8]) -> Self {
+            let mut account = SerializedAccount {
+                header: Box::new(SerializedHeader {
+                    padding: 0,
+                    original_data_len: data.len() as u32,
+                    key,
+                }),
+                buffer: vec![0; 1 + (data.len() + MAX_PERMITTED_DATA_INCREASE).div_ceil(8)],
+                lamports,
+                owner,
+            };
+            account.buffer[0] = data.len() as u64;
+            account.bytes()[8..8 + data.len()].copy_from_slice(data);
+            account
+        }
+
+        fn bytes(&mut self) -> &mut [u8] {
+            let len = self.buffer.len() * 8;
+            unsafe { std::slice::from_raw_parts_mut(self.buffer.as_mut_ptr().cast(), len) }
+        }
+
+        fn data(&mut self) -> &[u8] {
+            let len = self.buffer[0] as usize;
+            &self.bytes()[8..8 + len]
+        }
+
+        fn info(&mut self, is_signer: bool, is_writable: bool) -> AccountInfo<'_> {
+            let len = self.buffer[0] as usize;
+            let data = unsafe {
+                std::slice::from_raw_parts_mut(self.buffer.as_mut_ptr().cast::<u8>().add(8), len)
+            };
+            AccountInfo::new(
+                &self.header.key,
+                is_signer,
+                is_writable,
+                &mut self.lamports,
+                data,
+                &self.owner,
+                false,
+                0,
+            )
+        }
+    }
+
+    struct Accounts {
+        keys: [Pubkey; 3],
+        lamports: [u64; 3],
+        data: [Vec<u8>; 3],
+    }
+
+    impl Accounts {
+        fn new(result_len: usize) -> Self {
+            let mut result_data = vec![0xff; result_len];
+            result_data[..8].copy_from_slice(RESOLVER_RESULT_ACCOUNT);
+            Accounts {
+                keys: [
+                    result_account_address(&OWNER),
+                    Pubkey::new_unique(),
+                    system_program::ID,
+                ],
+                lamports: [1_000_000_000; 3],
+                data: [result_data, vec![], vec![]],
+            }
+        }
+
+        fn infos(&mut self, result_writable: bool) -> Vec<AccountInfo<'_>> {
+            let [result_key, payer_key, system_key] = &self.keys;
+            let [result_lamports, payer_lamports, system_lamports] = &mut self.lamports;
+            let [result_data, payer_data, system_data] = &mut self.data;
+            vec![
+                AccountInfo::new(
+                    result_key,
+                    false,
+                    result_writable,
+                    result_lamports,
+                    result_data,
+                    &OWNER,
+                    false,
+                    0,
+                ),
+                AccountInfo::new(
+                    payer_key,
+                    true,
+                    true,
+                    payer_lamports,
+                    payer_data,
+                    &system_program::ID,
+                    false,
+                    0,
+                ),
+                AccountInfo::new(
+                    system_key,
+                    false,
+                    false,
+                    system_lamports,
+                    system_data,
+                    &OWNER,
+                    true,
+                    0,
+                ),
+            ]
+        }
+    }
+
+    fn resolved() -> Resolver<InstructionGroups> {
+        Resolver::Resolved(InstructionGroups(vec![InstructionGroup {
+            instructions: vec![],
+            address_lookup_tables: vec![Pubkey::new_unique()],
+        }]))
+    }
+
+    #[test]
+    fn test_result_account_writer_write() {
+        let resolver = resolved();
+        let mut expected = RESOLVER_RESULT_ACCOUNT.to_vec();
+        resolver.serialize(&mut expected).unwrap();
+
+        let mut accounts = Accounts::new(100);
+        {
+            let infos = accounts.infos(true);
+            let writer = ResultAccountWriter::new(&OWNER, &infos[0], &infos[1], &infos[2]);
+            let returned: Resolver<InstructionGroups> = writer.write(&resolver).unwrap();
+            assert!(matches!(returned, Resolver::Account()));
+        }
+        // the account is large enough, so it is neither grown nor shrunk, and the stale bytes are zeroed
+        assert_eq!(accounts.data[0].len(), 100);
+        assert_eq!(accounts.data[0][..expected.len()], expected);
+        assert!(accounts.data[0][expected.len()..].iter().all(|b| *b == 0));
+        assert!(crate::decode_result_account(&accounts.data[0]).is_ok());
+        assert_eq!(accounts.lamports[0], 1_000_000_000);
+    }
+
+    #[test]
+    fn test_result_account_writer_grow() {
+        set_system_program_stubs();
+        let rent = Rent::default();
+        let resolver = resolved();
+        let size = RESOLVER_RESULT_ACCOUNT.len() + resolver.serialized_len();
+        let mut result = SerializedAccount::new(
+            result_account_address(&OWNER),
+            OWNER,
+            rent.minimum_balance(RESOLVER_RESULT_ACCOUNT.len()),
+            RESOLVER_RESULT_ACCOUNT,
+        );
+        let mut payer =
+            SerializedAccount::new(Pubkey::new_unique(), system_program::ID, 1_000_000_000, &[]);
+        let mut system = SerializedAccount::new(system_program::ID, OWNER, 1, &[]);
+        {
+            let infos = [
+                result.info(false, true),
+                payer.info(true, true),
+                system.info(false, false),
+            ];
+            let writer = ResultAccountWriter::new(&OWNER, &infos[0], &infos[1], &infos[2]);
+            let returned: Resolver<InstructionGroups> = writer.write(&resolver).unwrap();
+            assert_eq!(returned, Resolver::Account());
+        }
+        // the account is grown to exactly the result, and its rent is topped up by the payer
+        assert_eq!(result.data().len(), size);
+        assert_eq!(
+            crate::decode_result_account(result.data()).unwrap(),
+            resolver
+        );
+        assert_eq!(result.lamports, rent.minimum_balance(size));
+        assert_eq!(
+            payer.lamports,
+            1_000_000_000 - (rent.minimum_balance(size) - rent.minimum_balance(8))
+        );
+    }
+
+    #[test]
+    fn test_result_account_writer_write_at_create() {
+        set_system_program_stubs();
+        let rent = Rent::default();
+        let resolver = resolved();
+        let size = RESOLVER_RESULT_ACCOUNT.len() + resolver.serialized_len();
+        let (address, bump) = request_result_account_address(&OWNER, &[1; 32]);
+        let [prefix, request_seed] = request_result_account_seeds(&[1; 32]);
+        // a new account, one pre-funded by anyone, e.g. to block `create_account`, and one funded beyond its rent
+        for funded in [0, 1, 1_000_000_000] {
+            let mut result = SerializedAccount::new(address, system_program::ID, funded, &[]);
+            let mut payer = SerializedAccount::new(
+                Pubkey::new_unique(),
+                system_program::ID,
+                1_000_000_000,
+                &[],
+            );
+            let mut system = SerializedAccount::new(system_program::ID, OWNER, 1, &[]);
+            {
+                let infos = [
+                    result.info(false, true),
+                    payer.info(true, true),
+                    system.info(false, false),
+                ];
+                let writer = ResultAccountWriter::new(&OWNER, &infos[0], &infos[1], &infos[2]);
+                let returned: Resolver<InstructionGroups> = writer
+                    .write_at(&resolver, &[prefix, request_seed, &[bump]])
+                    .unwrap();
+                assert_eq!(returned, Resolver::AccountAt(address));
+            }
+            assert_eq!(result.owner, OWNER);
+            assert_eq!(result.data().len(), size);
+            assert_eq!(
+                crate::decode_result_account(result.data()).unwrap(),
+                resolver
+            );
+            let rent_exempt = u64::max(rent.minimum_balance(size), funded);
+            assert_eq!(result.lamports, rent_exempt);
+            assert_eq!(payer.lamports, 1_000_000_000 + funded - rent_exempt);
+        }
+
+        // the account must be the PDA of the seeds
+        let mut result = SerializedAccount::new(Pubkey::new_unique(), system_program::ID, 0, &[]);
+        let mut payer =
+            SerializedAccount::new(Pubkey::new_unique(), system_program::ID, 1_000_000_000, &[]);
+        let mut system = SerializedAccount::new(system_program::ID, OWNER, 1, &[]);
+        let infos = [
+            result.info(false, true),
+            payer.info(true, true),
+            system.info(false, false),
+        ];
+        let writer = ResultAccountWriter::new(&OWNER, &infos[0], &infos[1], &infos[2]);
+        assert_eq!(
+            writer
+                .write_at::<_, InstructionGroups>(&resolver, &[prefix, request_seed, &[bump]])
+                .err()
+                .unwrap(),
+            ErrorCode::ConstraintSeeds.into()
+        );
+    }
+
+    #[test]
+    fn test_result_account_writer_write_at() {
+        let resolver = resolved();
+        let (address, bump) = request_result_account_address(&OWNER, &[1; 32]);
+        let [prefix, request_seed] = request_result_account_seeds(&[1; 32]);
+        let mut accounts = Accounts::new(100);
+        accounts.keys[0] = address;
+        let infos = accounts.infos(true);
+        let writer = ResultAccountWriter::new(&OWNER, &infos[0], &infos[1], &infos[2]);
+        // an existing account is written like the canonical result account
+        let returned: Resolver<InstructionGroups> = writer
+            .write_at(&resolver, &[prefix, request_seed, &[bump]])
+            .unwrap();
+        assert_eq!(returned, Resolver::AccountAt(address));
+        // the account must be the PDA of the seeds
+        let [prefix, other_seed] = request_result_account_seeds(&[2; 32]);
+        assert_eq!(
+            writer
+                .write_at::<_, InstructionGroups>(&resolver, &[prefix, other_seed, &[bump]])
+                .err()
+                .unwrap(),
+            ErrorCode::ConstraintSeeds.into()
+        );
+    }
+
+    #[test]
+    fn test_return_or_write() {
+        let small = resolved();
+        assert!(small.serialized_len() <= MAX_RETURN_DATA);
+        let returned = return_or_write(small.clone(), &OWNER, &[]).unwrap();
+        assert_eq!(returned, small);
+
+        let large = Resolver::Resolved(InstructionGroups(vec![InstructionGroup {
+            instructions: vec![],
+            address_lookup_tables: vec![Pubkey::new_unique(); 40],
+        }]));
+        let mut expected = RESOLVER_RESULT_ACCOUNT.to_vec();
+        large.serialize(&mut expected).unwrap();
+        assert_eq!(large.serialized_len(), expected.len() - 8);
+        assert!(large.serialized_len() > MAX_RETURN_DATA);
+
+        let mut accounts = Accounts::new(2000);
+        accounts.keys[0] = result_account_address(&OWNER);
+        {
+            let infos = accounts.infos(true);
+            let missing = |remaining_accounts| match return_or_write(
+                large.clone(),
+                &OWNER,
+                remaining_accounts,
+            ) {
+                Ok(Resolver::Missing(missing)) => missing.accounts,
+                _ => panic!("expected missing accounts"),
+            };
+            assert_eq!(
+                missing(&[]),
+                [infos[0].key(), RESOLVER_PUBKEY_PAYER, system_program::ID]
+            );
+            assert_eq!(missing(&infos[..2]), [system_program::ID]);
+            assert_eq!(
+                missing(&[infos[2].clone(), infos[0].clone()]),
+                [RESOLVER_PUBKEY_PAYER]
+            );
+
+            // the accounts may be in any order, after the resolver's other accounts
+            let remaining_accounts = [infos[2].clone(), infos[1].clone(), infos[0].clone()];
+            let returned = return_or_write(large.clone(), &OWNER, &remaining_accounts).unwrap();
+            assert_eq!(returned, Resolver::Account());
+        }
+        assert_eq!(accounts.data[0][..expected.len()], expected);
+    }
+
+    #[test]
+    fn test_request_result_account_address() {
+        let program_id = Pubkey::new_unique();
+        let (address, bump) = request_result_account_address(&program_id, &[1; 32]);
+        let [prefix, request_seed] = request_result_account_seeds(&[1; 32]);
+        assert_eq!(
+            Pubkey::create_program_address(&[prefix, request_seed, &[bump]], &program_id).unwrap(),
+            address
+        );
+        assert_ne!(
+            request_result_account_address(&program_id, &[2; 32]).0,
+            address
+        );
+        assert_ne!(
+            Pubkey::find_program_address(&[RESOLVER_RESULT_ACCOUNT_SEED], &program_id).0,
+            address
+        );
+    }
+
+    #[test]
+    fn test_result_account_writer_not_writable() {
+        let mut accounts = Accounts::new(100);
+        let infos = accounts.infos(false);
+        let writer = ResultAccountWriter::new(&OWNER, &infos[0], &infos[1], &infos[2]);
+        assert_eq!(
+            writer
+                .write::<_, InstructionGroups>(&resolved())
+                .err()
+                .unwrap(),
+            ErrorCode::ConstraintMut.into()
+        );
+    }
+
+    #[test]
+    fn test_result_account_writer_invalid_owner() {
+        let mut accounts = Accounts::new(100);
+        let infos = accounts.infos(true);
+        let other_program = Pubkey::new_unique();
+        let writer = ResultAccountWriter::new(&other_program, &infos[0], &infos[1], &infos[2]);
+        assert_eq!(
+            writer
+                .write::<_, InstructionGroups>(&resolved())
+                .err()
+                .unwrap(),
+            ErrorCode::AccountOwnedByWrongProgram.into()
+        );
+    }
+
+    #[test]
+    fn test_result_account_writer_invalid_address() {
+        let mut accounts = Accounts::new(100);
+        accounts.keys[0] = Pubkey::new_unique();
+        let infos = accounts.infos(true);
+        let writer = ResultAccountWriter::new(&OWNER, &infos[0], &infos[1], &infos[2]);
+        assert_eq!(
+            writer
+                .write::<_, InstructionGroups>(&resolved())
+                .err()
+                .unwrap(),
+            ErrorCode::ConstraintSeeds.into()
+        );
+    }
+
+    #[test]
+    fn test_result_account_writer_invalid_discriminator() {
+        let mut accounts = Accounts::new(100);
+        accounts.data[0][0] ^= 1;
+        let infos = accounts.infos(true);
+        let writer = ResultAccountWriter::new(&OWNER, &infos[0], &infos[1], &infos[2]);
+        assert_eq!(
+            writer
+                .write::<_, InstructionGroups>(&resolved())
+                .err()
+                .unwrap(),
+            ErrorCode::AccountDiscriminatorMismatch.into()
+        );
+    }
+
+    #[test]
+    fn test_result_account_writer_too_large() {
+        let missing: Resolver<InstructionGroups> = Resolver::Missing(MissingAccounts {
+            accounts: vec![Pubkey::default(); MAX_PERMITTED_DATA_INCREASE / 32],
+            address_lookup_tables: vec![],
+        });
+        let mut accounts = Accounts::new(8);
+        let infos = accounts.infos(true);
+        let writer = ResultAccountWriter::new(&OWNER, &infos[0], &infos[1], &infos[2]);
+        assert_eq!(
+            writer
+                .write::<_, InstructionGroups>(&missing)
+                .err()
+                .unwrap(),
+            ProgramError::InvalidRealloc.into()
+        );
+    }
+
+    #[test]
+    fn test_result_account_writer_write_paginated() {
+        let resolver = resolved();
+        let mut expected = vec![];
+        resolver.serialize(&mut expected).unwrap();
+
+        let mut accounts = Accounts::new(100);
+        {
+            let infos = accounts.infos(true);
+            let writer = ResultAccountWriter::new(&OWNER, &infos[0], &infos[1], &infos[2]);
+            // the final chunk is written to the start of the account
+            let returned: Resolver<InstructionGroups> =
+                writer.write_paginated(&resolver, 10).unwrap();
+            assert!(matches!(returned, Resolver::Account()));
+            assert_eq!(
+                writer
+                    .write_paginated::<_, InstructionGroups>(&resolver, expected.len() as u32)
+                    .err()
+                    .unwrap(),
+                ProgramError::InvalidArgument.into()
+            );
+        }
+        assert_eq!(accounts.data[0][8..8 + expected.len() - 10], expected[10..]);
+        assert!(accounts.data[0][8 + expected.len() - 10..]
+            .iter()
+            .all(|b| *b == 0));
+    }
+
+    #[test]
+    fn test_window() {
+        let mut out = [0; 4];
+        let mut window = Window {
+            skip: 3,
+            out: &mut out,
+        };
+        for chunk in [&[1, 2][..], &[3, 4, 5], &[], &[6, 7, 8, 9]] {
+            assert_eq!(io::Write::write(&mut window, chunk).unwrap(), chunk.len());
+        }
+        assert_eq!(out, [4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn test_result_offset_marker() {
+        let marker = result_offset_marker(0x01020304);
+        assert!(marker.as_ref().starts_with(RESOLVER_RESULT_OFFSET_PREFIX));
+        assert_eq!(parse_result_offset_marker(&marker), Some(0x01020304));
+        assert_eq!(parse_result_offset_marker(&Pubkey::new_unique()), None);
+
+        let mut lamports = 0;
+        let mut data = vec![];
+        let marker_info = AccountInfo::new(
+            &marker,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &OWNER,
+            false,
+            0,
+        );
+        assert_eq!(result_offset(&[]), 0);
+        assert_eq!(result_offset(&[marker_info.clone()]), 0x01020304);
+        let mut accounts = Accounts::new(8);
+        let infos = accounts.infos(true);
+        assert_eq!(result_offset(&infos), 0);
+        assert_eq!(result_offset(&[marker_info, infos[0].clone()]), 0);
+    }
+}
@@ -0,0 +1,231 @@
+//! Dispatch of `RESOLVER_EXECUTE_VAA_V1` to per-message handlers for resolvers which accept several kinds of VAA.
+
+use anchor_lang::prelude::*;
+
+use crate::{unresolvable, InstructionGroups, Resolver, VaaBody, UNRESOLVABLE_UNSUPPORTED_PAYLOAD};
+
+/// A handler for the VAAs matched by a route. `C` is any context the handlers need,
+/// e.g. `&Context<Resolve>` or the `remaining_accounts`.
+pub type VaaHandler<C> = fn(C, VaaBody<'_>) -> Result<Resolver<InstructionGroups>>;
+
+struct Route<C> {
+    emitter_chain: u16,
+    emitter_address: [u8; 32],
+    payload_prefix: &'static [u8],
+    handler: VaaHandler<C>,
+}
+
+/// Routes a VAA body to the handler registered for its emitter and payload prefix.
+///
+/// Routes are matched in the order they were added, so a route with a longer prefix
+/// should be added before a route for the same emitter with a shorter prefix.
+/// If no route matches, the fallback handler is called if one was set, otherwise
+/// `Resolver::Unresolvable` is returned with `UNRESOLVABLE_UNSUPPORTED_PAYLOAD`, so relayers
+/// can tell a VAA this resolver never handles from a malformed one, which fails with its `VaaBodyError`.
+///
+/// Usage:
+///
+/// ```rust
+/// use anchor_lang::prelude::*;
+/// use executor_account_resolver_svm::{InstructionGroups, Resolver, VaaBody, VaaRouter};
+///
+/// const HUB_CHAIN: u16 = 2;
+/// const HUB_EMITTER: [u8; 32] = [1; 32];
+///
+/// fn resolve_transfer(
+///     remaining_accounts: &[AccountInfo],
+///     vaa: VaaBody,
+/// ) -> Result<Resolver<InstructionGroups>> {
+///     Ok(Resolver::Resolved(InstructionGroups(vec![])))
+/// }
+///
+/// fn resolve_governance(
+///     remaining_accounts: &[AccountInfo],
+///     vaa: VaaBody,
+/// ) -> Result<Resolver<InstructionGroups>> {
+///     Ok(Resolver::Resolved(InstructionGroups(vec![])))
+/// }
+///
+/// pub fn resolve_execute_vaa_v1(
+///     remaining_accounts: &[AccountInfo],
+///     vaa_body: Vec<u8>,
+/// ) -> Result<Resolver<InstructionGroups>> {
+///     VaaRouter::new()
+///         .route(HUB_CHAIN, HUB_EMITTER, &[1], resolve_transfer)
+///         .route(HUB_CHAIN, HUB_EMITTER, b"GOV", resolve_governance)
+///         .dispatch(remaining_accounts, &vaa_body)
+/// }
+/// ```
+pub struct VaaRouter<C> {
+    routes: Vec<Route<C>>,
+    fallback: Option<VaaHandler<C>>,
+}
+
+impl<C> Default for VaaRouter<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C> VaaRouter<C> {
+    pub fn new() -> Self {
+        VaaRouter {
+            routes: vec![],
+            fallback: None,
+        }
+    }
+
+    /// Routes VAAs from `emitter_address` on `emitter_chain` whose payload starts with `payload_prefix` to `handler`.
+    /// An empty prefix matches every payload from the emitter.
+    pub fn route(
+        mut self,
+        emitter_chain: u16,
+        emitter_address: [u8; 32],
+        payload_prefix: &'static [u8],
+        handler: VaaHandler<C>,
+    ) -> Self {
+        self.routes.push(Route {
+            emitter_chain,
+            emitter_address,
+            payload_prefix,
+            handler,
+        });
+        self
+    }
+
+    /// Handles VAAs which match no route, e.g. by returning `Resolver::Resolved(InstructionGroups(vec![]))`.
+    pub fn fallback(mut self, handler: VaaHandler<C>) -> Self {
+        self.fallback = Some(handler);
+        self
+    }
+
+    /// The handler for `vaa`, if any, without calling it.
+    pub fn find(&self, vaa: &VaaBody) -> Option<VaaHandler<C>> {
+        self.routes
+            .iter()
+            .find(|route| {
+                route.emitter_chain == vaa.emitter_chain()
+                    && route.emitter_address == *vaa.emitter_address()
+                    && vaa.payload().starts_with(route.payload_prefix)
+            })
+            .map(|route| route.handler)
+            .or(self.fallback)
+    }
+
+    /// Parses `vaa_body` and calls the matching handler with `context`.
+    pub fn dispatch(&self, context: C, vaa_body: &[u8]) -> Result<Resolver<InstructionGroups>> {
+        let vaa = VaaBody::parse(vaa_body)?;
+        match self.find(&vaa) {
+            Some(handler) => handler(context, vaa),
+            None => Ok(unresolvable(
+                UNRESOLVABLE_UNSUPPORTED_PAYLOAD,
+                &format!(
+                    "no route for VAA from emitter {} on chain {}",
+                    Pubkey::new_from_array(*vaa.emitter_address()),
+                    vaa.emitter_chain()
+                ),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::MissingAccounts;
+
+    const CHAIN: u16 = 2;
+    const EMITTER: [u8; 32] = [1; 32];
+
+    fn body(emitter_chain: u16, emitter_address: [u8; 32], payload: &[u8]) -> Vec<u8> {
+        let mut body = vec![0; 8];
+        body.extend_from_slice(&emitter_chain.to_be_bytes());
+        body.extend_from_slice(&emitter_address);
+        body.extend_from_slice(&[0; 9]);
+        body.extend_from_slice(payload);
+        body
+    }
+
+    /// Identifies the handler which was called by the number of missing accounts it returns.
+    fn missing(count: usize) -> Result<Resolver<InstructionGroups>> {
+        Ok(Resolver::Missing(MissingAccounts {
+            accounts: vec![Pubkey::default(); count],
+            address_lookup_tables: vec![],
+        }))
+    }
+
+    fn handled_by(result: Result<Resolver<InstructionGroups>>) -> usize {
+        match result.unwrap() {
+            Resolver::Missing(missing) => missing.accounts.len(),
+            _ => panic!("expected a test handler result"),
+        }
+    }
+
+    fn router() -> VaaRouter<()> {
+        VaaRouter::new()
+            .route(CHAIN, EMITTER, &[1, 2], |_, _| missing(1))
+            .route(CHAIN, EMITTER, &[1], |_, _| missing(2))
+            .route(CHAIN, [2; 32], &[], |_, vaa| {
+                missing(10 + vaa.payload().len())
+            })
+    }
+
+    #[test]
+    fn test_vaa_router_dispatch() {
+        let router = router();
+        assert_eq!(
+            handled_by(router.dispatch((), &body(CHAIN, EMITTER, &[1, 2, 3]))),
+            1
+        );
+        assert_eq!(
+            handled_by(router.dispatch((), &body(CHAIN, EMITTER, &[1, 3]))),
+            2
+        );
+        assert_eq!(
+            handled_by(router.dispatch((), &body(CHAIN, [2; 32], &[]))),
+            10
+        );
+        assert_eq!(
+            handled_by(router.dispatch((), &body(CHAIN, [2; 32], &[5, 6]))),
+            12
+        );
+    }
+
+    #[test]
+    fn test_vaa_router_no_route() {
+        let router = router();
+        for vaa_body in [
+            body(CHAIN, EMITTER, &[2]),
+            body(CHAIN, EMITTER, &[]),
+            body(CHAIN + 1, EMITTER, &[1, 2]),
+            body(CHAIN, [3; 32], &[1, 2]),
+        ] {
+            let vaa = VaaBody::parse(&vaa_body).unwrap();
+            assert!(router.find(&vaa).is_none());
+            assert_eq!(
+                router.dispatch((), &vaa_body).unwrap(),
+                Resolver::Unresolvable {
+                    code: UNRESOLVABLE_UNSUPPORTED_PAYLOAD,
+                    reason: format!(
+                        "no route for VAA from emitter {} on chain {}",
+                        Pubkey::new_from_array(*vaa.emitter_address()),
+                        vaa.emitter_chain()
+                    ),
+                }
+            );
+        }
+        let router = router.fallback(|_, _| missing(0));
+        assert_eq!(
+            handled_by(router.dispatch((), &body(CHAIN, EMITTER, &[]))),
+            0
+        );
+    }
+
+    #[test]
+    fn test_vaa_router_invalid_body() {
+        assert_eq!(
+            router().dispatch((), &[0; 50]).err().unwrap(),
+            ProgramError::InvalidInstructionData.into()
+        );
+    }
+}
@@ -0,0 +1,189 @@
+//! Estimation of the serialized size of the v0 transaction a relayer will build for an `InstructionGroup`,
+//! so resolver authors can check that their worst-case groups fit within `MAX_TRANSACTION_SIZE`.
+
+use std::collections::BTreeMap;
+
+use anchor_lang::prelude::*;
+
+use crate::{InstructionGroup, ResolverPlaceholder, RESOLVER_PUBKEY_PAYER};
+
+/// The size of a signature in a serialized transaction.
+const SIGNATURE_SIZE: usize = 64;
+
+/// The number of entries in a lookup table which can be referenced by a `u8` index.
+const MAX_LOOKUP_TABLE_INDEXES: usize = 256;
+
+#[derive(Default, Clone, Copy)]
+struct KeyFlags {
+    is_signer: bool,
+    is_writable: bool,
+    is_invoked: bool,
+}
+
+/// Returns the exact serialized size, in bytes, of the signed v0 transaction for `group`,
+/// compiled the same way as `solana_message::v0::Message::try_compile` with `RESOLVER_PUBKEY_PAYER` as the fee payer.
+///
+/// `lookup_tables` contains the addresses of each table in `group.address_lookup_tables`, in the same order.
+/// Non-signer, non-program accounts found in a table are loaded from it instead of being included in the message.
+/// Placeholders are never loaded from a table, as they are replaced by the relayer.
+/// The signature count is the payer plus every distinct signer in the group, e.g. keypair placeholders.
+///
+/// Usage:
+///
+/// ```rust
+/// use anchor_lang::prelude::*;
+/// use executor_account_resolver_svm::{
+///     estimate_transaction_size, InstructionGroup, MAX_TRANSACTION_SIZE,
+/// };
+///
+/// fn assert_fits(group: &InstructionGroup, lookup_table: &[Pubkey]) {
+///     assert!(estimate_transaction_size(group, &[lookup_table]) <= MAX_TRANSACTION_SIZE);
+/// }
+/// ```
+pub fn estimate_transaction_size(group: &InstructionGroup, lookup_tables: &[&[Pubkey]]) -> usize {
+    let mut keys: BTreeMap<Pubkey, KeyFlags> = BTreeMap::new();
+    keys.insert(
+        RESOLVER_PUBKEY_PAYER,
+        KeyFlags {
+            is_signer: true,
+            is_writable: true,
+            is_invoked: false,
+        },
+    );
+    for instruction in &group.instructions {
+        keys.entry(instruction.program_id).or_default().is_invoked = true;
+        for meta in &instruction.accounts {
+            let flags = keys.entry(meta.pubkey).or_default();
+            flags.is_signer |= meta.is_signer;
+            flags.is_writable |= meta.is_writable;
+        }
+    }
+    let num_signatures = keys.values().filter(|flags| flags.is_signer).count();
+
+    let mut lookups_size = 0;
+    let mut num_lookups = 0;
+    for addresses in lookup_tables.iter().take(group.address_lookup_tables.len()) {
+        let addresses = &addresses[..addresses.len().min(MAX_LOOKUP_TABLE_INDEXES)];
+        let mut writable = 0;
+        let mut readonly = 0;
+        keys.retain(|key, flags| {
+            if flags.is_signer
+                || flags.is_invoked
+                || ResolverPlaceholder::from_pubkey(key).is_some()
+                || !addresses.contains(key)
+            {
+                return true;
+            }
+            if flags.is_writable {
+                writable += 1;
+            } else {
+                readonly += 1;
+            }
+            false
+        });
+        // a table which covers no accounts is omitted from the message
+        if writable + readonly > 0 {
+            num_lookups += 1;
+            lookups_size +=
+                32 + short_vec_len(writable) + writable + short_vec_len(readonly) + readonly;
+        }
+    }
+
+    let instructions_size: usize = group
+        .instructions
+        .iter()
+        .map(|instruction| {
+            1 + short_vec_len(instruction.accounts.len())
+                + instruction.accounts.len()
+                + short_vec_len(instruction.data.len())
+                + instruction.data.len()
+        })
+        .sum();
+
+    let message_size = 1 // version prefix
+        + 3 // header
+        + short_vec_len(keys.len())
+        + keys.len() * 32
+        + 32 // recent blockhash
+        + short_vec_len(group.instructions.len())
+        + instructions_size
+        + short_vec_len(num_lookups)
+        + lookups_size;
+
+    short_vec_len(num_signatures) + num_signatures * SIGNATURE_SIZE + message_size
+}
+
+/// The length of a compact-u16 encoded length prefix.
+pub(crate) fn short_vec_len(len: usize) -> usize {
+    match len {
+        0..=0x7f => 1,
+        0x80..=0x3fff => 2,
+        _ => 3,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        SerializableAccountMeta, SerializableInstruction, MAX_TRANSACTION_SIZE,
+        RESOLVER_PUBKEY_KEYPAIR_00,
+    };
+
+    fn meta(pubkey: Pubkey, is_signer: bool, is_writable: bool) -> SerializableAccountMeta {
+        SerializableAccountMeta {
+            pubkey,
+            is_signer,
+            is_writable,
+        }
+    }
+
+    #[test]
+    fn test_short_vec_len() {
+        assert_eq!(short_vec_len(0), 1);
+        assert_eq!(short_vec_len(0x7f), 1);
+        assert_eq!(short_vec_len(0x80), 2);
+        assert_eq!(short_vec_len(0x3fff), 2);
+        assert_eq!(short_vec_len(0x4000), 3);
+    }
+
+    #[test]
+    fn test_estimate_transaction_size() {
+        let program_id = Pubkey::new_unique();
+        let table_key = Pubkey::new_unique();
+        let table: Vec<Pubkey> = (0..4).map(|_| Pubkey::new_unique()).collect();
+        let group = InstructionGroup {
+            instructions: vec![SerializableInstruction {
+                program_id,
+                accounts: vec![
+                    meta(RESOLVER_PUBKEY_PAYER, true, true),
+                    meta(RESOLVER_PUBKEY_KEYPAIR_00, true, true),
+                    meta(table[0], false, true),
+                    meta(table[1], false, false),
+                    meta(Pubkey::new_unique(), false, false),
+                ],
+                data: vec![0; 10],
+            }],
+            address_lookup_tables: vec![table_key],
+        };
+        let without_table = InstructionGroup {
+            address_lookup_tables: vec![],
+            ..group.clone()
+        };
+        // 2 signatures + header + 6 static keys + blockhash + instruction, without lookups
+        let static_size = 1 + 2 * 64 + 1 + 3 + 1 + 6 * 32 + 32 + 1 + (1 + 1 + 5 + 1 + 10) + 1;
+        assert_eq!(estimate_transaction_size(&without_table, &[]), static_size);
+        // two keys move into the lookup table
+        assert_eq!(
+            estimate_transaction_size(&group, &[&table]),
+            static_size - 2 * 32 + (32 + 1 + 1 + 1 + 1)
+        );
+        // placeholders are never looked up
+        let placeholder_table = [RESOLVER_PUBKEY_KEYPAIR_00, RESOLVER_PUBKEY_PAYER];
+        assert_eq!(
+            estimate_transaction_size(&group, &[&placeholder_table]),
+            static_size
+        );
+        assert!(static_size <= MAX_TRANSACTION_SIZE);
+    }
+}
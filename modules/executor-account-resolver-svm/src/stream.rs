@@ -0,0 +1,377 @@
+//! Incremental serialization of `Resolver::Resolved(InstructionGroups)` directly into a buffer,
+//! e.g. the result account data, without building the `InstructionGroups` in memory.
+
+use std::fmt;
+
+use anchor_lang::prelude::*;
+
+use crate::SerializableAccountMeta;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamError {
+    /// The buffer cannot hold the next write.
+    BufferFull { needed: usize, remaining: usize },
+}
+
+impl fmt::Display for StreamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StreamError::BufferFull { needed, remaining } => write!(
+                f,
+                "result buffer is full: {needed} bytes needed but {remaining} remaining"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for StreamError {}
+
+impl From<StreamError> for anchor_lang::error::Error {
+    fn from(err: StreamError) -> Self {
+        msg!("{}", err);
+        ProgramError::AccountDataTooSmall.into()
+    }
+}
+
+/// The write position in the buffer. `reserved` bytes at the end are held back for the
+/// length prefixes which are written when a writer is dropped, so dropping can never fail.
+struct Cursor<'a> {
+    buf: &'a mut [u8],
+    pos: usize,
+    reserved: usize,
+}
+
+impl Cursor<'_> {
+    fn ensure(&self, len: usize) -> std::result::Result<(), StreamError> {
+        let remaining = self.buf.len() - self.pos - self.reserved;
+        if len > remaining {
+            return Err(StreamError::BufferFull {
+                needed: len,
+                remaining,
+            });
+        }
+        Ok(())
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> std::result::Result<(), StreamError> {
+        self.ensure(bytes.len())?;
+        self.buf[self.pos..self.pos + bytes.len()].copy_from_slice(bytes);
+        self.pos += bytes.len();
+        Ok(())
+    }
+
+    /// Writes a zero length prefix to be patched later, returning its position.
+    fn write_prefix(&mut self) -> std::result::Result<usize, StreamError> {
+        let at = self.pos;
+        self.write(&0u32.to_le_bytes())?;
+        Ok(at)
+    }
+
+    fn patch_prefix(&mut self, at: usize, len: u32) {
+        self.buf[at..at + 4].copy_from_slice(&len.to_le_bytes());
+    }
+
+    fn reserve_prefix(&mut self) -> std::result::Result<(), StreamError> {
+        self.ensure(4)?;
+        self.reserved += 4;
+        Ok(())
+    }
+
+    /// Writes a previously reserved prefix.
+    fn write_reserved_prefix(&mut self, len: u32) {
+        self.reserved -= 4;
+        self.buf[self.pos..self.pos + 4].copy_from_slice(&len.to_le_bytes());
+        self.pos += 4;
+    }
+}
+
+/// Writes `Resolver::Resolved(InstructionGroups(..))` group by group, back-patching each
+/// Borsh vector length prefix once its elements have been written.
+///
+/// Each writer finalizes its part of the output when it is dropped: an `InstructionWriter`
+/// without `data` has empty data, and a `GroupWriter` without `lookup_tables` has no lookup tables.
+/// The output is only valid once the `InstructionGroupsWriter` has been finished or dropped.
+///
+/// Usage:
+///
+/// ```rust
+/// use anchor_lang::prelude::*;
+/// use executor_account_resolver_svm::{
+///     InstructionGroups, InstructionGroupsWriter, Resolver, ResultAccountWriter,
+///     SerializableAccountMeta, RESOLVER_RESULT_ACCOUNT,
+/// };
+///
+/// pub fn resolve_execute_vaa_v1<'info>(
+///     result: &AccountInfo<'info>,
+///     payer: &AccountInfo<'info>,
+///     system_program: &AccountInfo<'info>,
+///     recipients: &[Pubkey],
+/// ) -> Result<Resolver<InstructionGroups>> {
+///     let program_id = crate_id();
+///     let writer = ResultAccountWriter::new(&program_id, result, payer, system_program);
+///     // the variant and group count, then 83 bytes per group
+///     writer.reserve(5 + recipients.len() * 83)?;
+///     let mut data = result.try_borrow_mut_data()?;
+///     let mut groups = InstructionGroupsWriter::new(&mut data[RESOLVER_RESULT_ACCOUNT.len()..])?;
+///     for recipient in recipients {
+///         let mut group = groups.group()?;
+///         let mut instruction = group.instruction(&crate_id())?;
+///         instruction.account(&SerializableAccountMeta {
+///             pubkey: *recipient,
+///             is_signer: false,
+///             is_writable: true,
+///         })?;
+///         instruction.data(&[1])?;
+///     }
+///     groups.finish();
+///     Ok(Resolver::Account())
+/// }
+/// # fn crate_id() -> Pubkey { Pubkey::default() }
+/// ```
+pub struct InstructionGroupsWriter<'a> {
+    cursor: Cursor<'a>,
+    prefix_at: usize,
+    count: u32,
+}
+
+impl<'a> InstructionGroupsWriter<'a> {
+    /// Starts writing `Resolver::Resolved` at the beginning of `buf`.
+    pub fn new(buf: &'a mut [u8]) -> std::result::Result<Self, StreamError> {
+        let mut cursor = Cursor {
+            buf,
+            pos: 0,
+            reserved: 0,
+        };
+        // `Resolver::Resolved`
+        cursor.write(&[0])?;
+        let prefix_at = cursor.write_prefix()?;
+        Ok(InstructionGroupsWriter {
+            cursor,
+            prefix_at,
+            count: 0,
+        })
+    }
+
+    /// Starts the next `InstructionGroup`.
+    pub fn group(&mut self) -> std::result::Result<GroupWriter<'_, 'a>, StreamError> {
+        let prefix_at = self.cursor.write_prefix()?;
+        if let Err(err) = self.cursor.reserve_prefix() {
+            self.cursor.pos = prefix_at;
+            return Err(err);
+        }
+        self.count += 1;
+        Ok(GroupWriter {
+            cursor: &mut self.cursor,
+            prefix_at,
+            count: 0,
+            closed: false,
+        })
+    }
+
+    /// Finalizes the output, returning its length.
+    pub fn finish(self) -> usize {
+        let len = self.cursor.pos;
+        drop(self);
+        len
+    }
+}
+
+impl Drop for InstructionGroupsWriter<'_> {
+    fn drop(&mut self) {
+        self.cursor.patch_prefix(self.prefix_at, self.count);
+    }
+}
+
+/// Writes the instructions and then the lookup tables of an `InstructionGroup`.
+pub struct GroupWriter<'w, 'a> {
+    cursor: &'w mut Cursor<'a>,
+    prefix_at: usize,
+    count: u32,
+    closed: bool,
+}
+
+impl<'a> GroupWriter<'_, 'a> {
+    /// Starts the next instruction of the group.
+    pub fn instruction(
+        &mut self,
+        program_id: &Pubkey,
+    ) -> std::result::Result<InstructionWriter<'_, 'a>, StreamError> {
+        self.cursor.ensure(32 + 4 + 4)?;
+        self.cursor.write(program_id.as_ref())?;
+        let prefix_at = self.cursor.write_prefix()?;
+        self.cursor.reserve_prefix()?;
+        self.count += 1;
+        Ok(InstructionWriter {
+            cursor: &mut *self.cursor,
+            prefix_at,
+            count: 0,
+            closed: false,
+        })
+    }
+
+    /// Writes the group's lookup tables, completing the group.
+    pub fn lookup_tables(
+        mut self,
+        address_lookup_tables: &[Pubkey],
+    ) -> std::result::Result<(), StreamError> {
+        self.cursor.ensure(address_lookup_tables.len() * 32)?;
+        self.close(address_lookup_tables.len() as u32);
+        for key in address_lookup_tables {
+            self.cursor.write(key.as_ref())?;
+        }
+        Ok(())
+    }
+
+    fn close(&mut self, lookup_tables: u32) {
+        self.cursor.patch_prefix(self.prefix_at, self.count);
+        self.cursor.write_reserved_prefix(lookup_tables);
+        self.closed = true;
+    }
+}
+
+impl Drop for GroupWriter<'_, '_> {
+    fn drop(&mut self) {
+        if !self.closed {
+            self.close(0);
+        }
+    }
+}
+
+/// Writes the accounts and then the data of a `SerializableInstruction`.
+pub struct InstructionWriter<'w, 'a> {
+    cursor: &'w mut Cursor<'a>,
+    prefix_at: usize,
+    count: u32,
+    closed: bool,
+}
+
+impl InstructionWriter<'_, '_> {
+    pub fn account(
+        &mut self,
+        meta: &SerializableAccountMeta,
+    ) -> std::result::Result<(), StreamError> {
+        self.cursor.ensure(34)?;
+        self.cursor.write(meta.pubkey.as_ref())?;
+        self.cursor
+            .write(&[u8::from(meta.is_signer), u8::from(meta.is_writable)])?;
+        self.count += 1;
+        Ok(())
+    }
+
+    /// Writes the instruction data, completing the instruction.
+    pub fn data(mut self, data: &[u8]) -> std::result::Result<(), StreamError> {
+        self.cursor.ensure(data.len())?;
+        self.close(data.len() as u32);
+        self.cursor.write(data)
+    }
+
+    fn close(&mut self, data_len: u32) {
+        self.cursor.patch_prefix(self.prefix_at, self.count);
+        self.cursor.write_reserved_prefix(data_len);
+        self.closed = true;
+    }
+}
+
+impl Drop for InstructionWriter<'_, '_> {
+    fn drop(&mut self) {
+        if !self.closed {
+            self.close(0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{InstructionGroup, InstructionGroups, Resolver, SerializableInstruction};
+
+    fn meta(n: u8) -> SerializableAccountMeta {
+        SerializableAccountMeta {
+            pubkey: Pubkey::new_from_array([n; 32]),
+            is_signer: n % 2 == 0,
+            is_writable: n % 3 == 0,
+        }
+    }
+
+    fn expected() -> Vec<u8> {
+        let resolver: Resolver<InstructionGroups> = Resolver::Resolved(InstructionGroups(vec![
+            InstructionGroup {
+                instructions: vec![
+                    SerializableInstruction {
+                        program_id: Pubkey::new_from_array([1; 32]),
+                        accounts: vec![meta(2), meta(3), meta(4)],
+                        data: vec![5, 6, 7],
+                    },
+                    SerializableInstruction {
+                        program_id: Pubkey::new_from_array([8; 32]),
+                        accounts: vec![],
+                        data: vec![],
+                    },
+                ],
+                address_lookup_tables: vec![Pubkey::new_from_array([9; 32])],
+            },
+            InstructionGroup {
+                instructions: vec![SerializableInstruction {
+                    program_id: Pubkey::new_from_array([10; 32]),
+                    accounts: vec![meta(11)],
+                    data: vec![],
+                }],
+                address_lookup_tables: vec![],
+            },
+            InstructionGroup {
+                instructions: vec![],
+                address_lookup_tables: vec![],
+            },
+        ]));
+        let mut buffer = vec![];
+        resolver.serialize(&mut buffer).unwrap();
+        buffer
+    }
+
+    fn stream(buf: &mut [u8]) -> std::result::Result<usize, StreamError> {
+        let mut groups = InstructionGroupsWriter::new(buf)?;
+        {
+            let mut group = groups.group()?;
+            let mut instruction = group.instruction(&Pubkey::new_from_array([1; 32]))?;
+            for n in 2..5 {
+                instruction.account(&meta(n))?;
+            }
+            instruction.data(&[5, 6, 7])?;
+            group.instruction(&Pubkey::new_from_array([8; 32]))?;
+            group.lookup_tables(&[Pubkey::new_from_array([9; 32])])?;
+        }
+        {
+            let mut group = groups.group()?;
+            let mut instruction = group.instruction(&Pubkey::new_from_array([10; 32]))?;
+            instruction.account(&meta(11))?;
+        }
+        groups.group()?;
+        Ok(groups.finish())
+    }
+
+    #[test]
+    fn test_instruction_groups_writer_matches_borsh() {
+        let expected = expected();
+        let mut buf = vec![0xff; 1024];
+        let len = stream(&mut buf).unwrap();
+        assert_eq!(len, expected.len());
+        assert_eq!(buf[..len], expected);
+
+        // an exactly sized buffer is sufficient
+        let mut buf = vec![0; expected.len()];
+        assert_eq!(stream(&mut buf).unwrap(), expected.len());
+        assert_eq!(buf, expected);
+    }
+
+    #[test]
+    fn test_instruction_groups_writer_buffer_full() {
+        let len = expected().len();
+        for size in 0..len {
+            let mut buf = vec![0; size];
+            assert!(
+                matches!(stream(&mut buf), Err(StreamError::BufferFull { .. })),
+                "{size} byte buffer"
+            );
+        }
+    }
+}
@@ -0,0 +1,241 @@
+//! A zero-copy view of the body of a Wormhole v1 VAA, as passed to `RESOLVER_EXECUTE_VAA_V1`.
+
+use std::fmt;
+
+#[allow(deprecated)]
+use anchor_lang::{prelude::*, solana_program::keccak};
+
+/// A zero-copy view of a v1 VAA body.
+///
+/// The body is laid out as follows, with all integers big-endian.
+///
+/// | offset | size | field             |
+/// | ------ | ---- | ----------------- |
+/// | 0      | 4    | timestamp         |
+/// | 4      | 4    | nonce             |
+/// | 8      | 2    | emitter chain     |
+/// | 10     | 32   | emitter address   |
+/// | 42     | 8    | sequence          |
+/// | 50     | 1    | consistency level |
+/// | 51     | ..   | payload           |
+///
+/// Usage:
+///
+/// ```rust
+/// use anchor_lang::prelude::*;
+/// use executor_account_resolver_svm::{InstructionGroups, Resolver, VaaBody};
+///
+/// pub fn resolve_execute_vaa_v1(vaa_body: Vec<u8>) -> Result<Resolver<InstructionGroups>> {
+///     let vaa = VaaBody::parse(&vaa_body)?;
+///     let amount = u64::from_be_bytes(*vaa.payload_array::<8>(1)?);
+///     msg!("{} from chain {}", amount, vaa.emitter_chain());
+///     Ok(Resolver::Resolved(InstructionGroups(vec![])))
+/// }
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct VaaBody<'a>(&'a [u8]);
+
+impl<'a> VaaBody<'a> {
+    /// The length of the body before the payload.
+    pub const HEADER_LEN: usize = 51;
+
+    /// Checks that `body` is long enough to contain every field.
+    pub fn parse(body: &'a [u8]) -> std::result::Result<Self, VaaBodyError> {
+        if body.len() < Self::HEADER_LEN {
+            return Err(VaaBodyError::TooShort { len: body.len() });
+        }
+        Ok(VaaBody(body))
+    }
+
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.0
+    }
+
+    pub fn timestamp(&self) -> u32 {
+        u32::from_be_bytes(*self.array(0))
+    }
+
+    pub fn nonce(&self) -> u32 {
+        u32::from_be_bytes(*self.array(4))
+    }
+
+    pub fn emitter_chain(&self) -> u16 {
+        u16::from_be_bytes(*self.array(8))
+    }
+
+    pub fn emitter_address(&self) -> &'a [u8; 32] {
+        self.array(10)
+    }
+
+    pub fn sequence(&self) -> u64 {
+        u64::from_be_bytes(*self.array(42))
+    }
+
+    pub fn consistency_level(&self) -> u8 {
+        self.0[50]
+    }
+
+    pub fn payload(&self) -> &'a [u8] {
+        &self.0[Self::HEADER_LEN..]
+    }
+
+    /// Reads `N` bytes of the payload starting at `offset`.
+    pub fn payload_array<const N: usize>(
+        &self,
+        offset: usize,
+    ) -> std::result::Result<&'a [u8; N], VaaBodyError> {
+        let payload = self.payload();
+        offset
+            .checked_add(N)
+            .and_then(|end| payload.get(offset..end))
+            .map(|bytes| bytes.try_into().unwrap())
+            .ok_or(VaaBodyError::PayloadOutOfBounds {
+                offset,
+                len: N,
+                payload_len: payload.len(),
+            })
+    }
+
+    /// The keccak256 hash of the body, used by the Core Bridge to derive the posted VAA account.
+    pub fn message_hash(&self) -> [u8; 32] {
+        keccak::hash(self.0).to_bytes()
+    }
+
+    /// The keccak256 hash of `message_hash`, which is signed by the guardians.
+    pub fn digest(&self) -> [u8; 32] {
+        keccak::hash(&self.message_hash()).to_bytes()
+    }
+
+    fn array<const N: usize>(&self, offset: usize) -> &'a [u8; N] {
+        self.0[offset..offset + N].try_into().unwrap()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VaaBodyError {
+    /// The body is shorter than `VaaBody::HEADER_LEN`.
+    TooShort { len: usize },
+    /// A read of `len` bytes at `offset` extends past the end of the payload.
+    PayloadOutOfBounds {
+        offset: usize,
+        len: usize,
+        payload_len: usize,
+    },
+}
+
+impl fmt::Display for VaaBodyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VaaBodyError::TooShort { len } => write!(
+                f,
+                "VAA body is {len} bytes, shorter than its {} byte header",
+                VaaBody::HEADER_LEN
+            ),
+            VaaBodyError::PayloadOutOfBounds {
+                offset,
+                len,
+                payload_len,
+            } => write!(
+                f,
+                "cannot read {len} bytes at offset {offset} of a {payload_len} byte payload"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for VaaBodyError {}
+
+impl From<VaaBodyError> for anchor_lang::error::Error {
+    fn from(err: VaaBodyError) -> Self {
+        msg!("{}", err);
+        ProgramError::InvalidInstructionData.into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn body(payload: &[u8]) -> Vec<u8> {
+        let mut body = vec![];
+        body.extend_from_slice(&1_700_000_000u32.to_be_bytes());
+        body.extend_from_slice(&42u32.to_be_bytes());
+        body.extend_from_slice(&2u16.to_be_bytes());
+        body.extend_from_slice(&[0xab; 32]);
+        body.extend_from_slice(&1234u64.to_be_bytes());
+        body.push(15);
+        body.extend_from_slice(payload);
+        body
+    }
+
+    #[test]
+    fn test_vaa_body_fields() {
+        let bytes = body(&[1, 2, 3]);
+        let vaa = VaaBody::parse(&bytes).unwrap();
+        assert_eq!(vaa.timestamp(), 1_700_000_000);
+        assert_eq!(vaa.nonce(), 42);
+        assert_eq!(vaa.emitter_chain(), 2);
+        assert_eq!(vaa.emitter_address(), &[0xab; 32]);
+        assert_eq!(vaa.sequence(), 1234);
+        assert_eq!(vaa.consistency_level(), 15);
+        assert_eq!(vaa.payload(), &[1, 2, 3]);
+        assert_eq!(vaa.as_bytes(), &bytes[..]);
+    }
+
+    #[test]
+    fn test_vaa_body_too_short() {
+        let bytes = body(&[]);
+        assert!(VaaBody::parse(&bytes).unwrap().payload().is_empty());
+        assert_eq!(
+            VaaBody::parse(&bytes[..50]).unwrap_err(),
+            VaaBodyError::TooShort { len: 50 }
+        );
+    }
+
+    #[test]
+    fn test_vaa_body_payload_array() {
+        let bytes = body(&[1, 2, 3, 4]);
+        let vaa = VaaBody::parse(&bytes).unwrap();
+        assert_eq!(vaa.payload_array::<2>(2).unwrap(), &[3, 4]);
+        assert!(vaa.payload_array::<0>(4).is_ok());
+        assert_eq!(
+            vaa.payload_array::<2>(3).unwrap_err(),
+            VaaBodyError::PayloadOutOfBounds {
+                offset: 3,
+                len: 2,
+                payload_len: 4
+            }
+        );
+        assert!(vaa.payload_array::<1>(usize::MAX).is_err());
+    }
+
+    fn hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_vaa_body_digest() {
+        // a transfer of 1_000_000 from the Ethereum token bridge emitter, hashed independently of this crate
+        let bytes = hex(concat!(
+            "65f2a8c0",
+            "00000001",
+            "0002",
+            "0000000000000000000000003ee18b2214aff97000d974cf647e7c347e8fa585",
+            "0000000000000042",
+            "01",
+            "0100000000000000000000000000000000000000000000000000000000000f4240",
+        ));
+        let vaa = VaaBody::parse(&bytes).unwrap();
+        assert_eq!(
+            vaa.message_hash().to_vec(),
+            hex("7a7a099274c58e6939246629ccc4e79a8ea679eb52a6c4fb7d6e955848c9dbdc")
+        );
+        assert_eq!(
+            vaa.digest().to_vec(),
+            hex("fdf161d253eda3d75b2c970658b7e70052987026e8e05615e26289c3ef1539e8")
+        );
+    }
+}
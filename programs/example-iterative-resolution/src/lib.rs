@@ -1,7 +1,7 @@
-use anchor_lang::{prelude::*, solana_program::instruction::Instruction, InstructionData};
+use anchor_lang::{prelude::*, InstructionData};
 use executor_account_resolver_svm::{
-    find_account, missing_account, InstructionGroup, InstructionGroups, Resolver,
-    RESOLVER_EXECUTE_VAA_V1, RESOLVER_PUBKEY_PAYER,
+    resolve_missing, InstructionGroup, InstructionGroups, ResolveAccounts, ResolveResult, Resolver,
+    RESOLVER_EXECUTE_VAA_V1,
 };
 
 declare_id!("8mjNDtRMN7Sjq2ZVjCjKJUUaCfUdfZLoeYREmYs3yKSi");
@@ -19,11 +19,11 @@ pub mod example_iterative_resolution {
     }
 
     #[instruction(discriminator = &RESOLVER_EXECUTE_VAA_V1)]
-    pub fn accounts_to_execute(
-        ctx: Context<Resolve>,
+    pub fn accounts_to_execute<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Resolve>,
         _vaa_body: Vec<u8>,
     ) -> Result<Resolver<InstructionGroups>> {
-        Ok(accounts_to_execute2(ctx))
+        resolve_missing(accounts_to_execute2(ctx))
     }
 
     pub fn example_instruction(ctx: Context<ExampleInstruction>) -> Result<()> {
@@ -32,48 +32,28 @@ pub mod example_iterative_resolution {
     }
 }
 
-pub fn accounts_to_execute2(ctx: Context<Resolve>) -> Resolver<InstructionGroups> {
+pub fn accounts_to_execute2<'info>(
+    ctx: Context<'_, '_, 'info, 'info, Resolve>,
+) -> ResolveResult<Resolver<InstructionGroups>> {
     // This example iteratively loads the accounts, as it simulates a condition where looking up a subsequent account
     // relies on data within a previous account.
     //
     // See example-lookup-table-resolution for requesting and resolving multiple accounts at once
     // in addition to handling results larger than the instruction return size of 1024.
-    let (foo_key, _) = Pubkey::find_program_address(&[b"foo"], &crate::ID);
-    let foo = if let Some(acc_info) = find_account(ctx.remaining_accounts, foo_key) {
-        MyAccount::try_deserialize(&mut &acc_info.data.borrow()[..]).unwrap()
-    } else {
-        return missing_account(foo_key);
-    };
-    let (bar_key, _) = Pubkey::find_program_address(&[b"bar", &[foo.data]], &crate::ID);
-    let bar = if let Some(acc_info) = find_account(ctx.remaining_accounts, bar_key) {
-        MyAccount::try_deserialize(&mut &acc_info.data.borrow()[..]).unwrap()
-    } else {
-        return missing_account(bar_key);
-    };
-    let (baz_key, _) = Pubkey::find_program_address(&[b"baz", &[bar.data]], &crate::ID);
-    let baz = if let Some(acc_info) = find_account(ctx.remaining_accounts, baz_key) {
-        MyAccount::try_deserialize(&mut &acc_info.data.borrow()[..]).unwrap()
-    } else {
-        return missing_account(baz_key);
-    };
-    let (qux_key, _) = Pubkey::find_program_address(&[b"qux", &[baz.data]], &crate::ID);
-    let accs = accounts::ExampleInstruction {
-        payer: RESOLVER_PUBKEY_PAYER,
-        foo: foo_key,
-        bar: bar_key,
-        baz: baz_key,
-        qux: qux_key,
-        system_program: System::id(),
-    };
-    let instruction: Instruction = Instruction {
-        program_id: crate::ID,
-        accounts: accs.to_account_metas(None),
-        data: (instruction::ExampleInstruction {}).data(),
-    };
-    Resolver::Resolved(InstructionGroups(vec![InstructionGroup {
-        instructions: vec![instruction.into()],
-        address_lookup_tables: vec![],
-    }]))
+    // Accounts which do not depend on each other can be requested in a single pass with `MissingCollector`.
+    //
+    // The accounts are derived from the seeds of `ExampleInstruction` by `ResolveAccounts`, which requests
+    // `foo`, `bar` and `baz` in turn since each is needed to derive the next.
+    let instruction = ExampleInstruction::resolve_instruction(
+        ctx.remaining_accounts,
+        (instruction::ExampleInstruction {}).data(),
+    )?;
+    Ok(Resolver::Resolved(InstructionGroups(vec![
+        InstructionGroup {
+            instructions: vec![instruction],
+            address_lookup_tables: vec![],
+        },
+    ])))
 }
 
 #[account]
@@ -117,7 +97,7 @@ pub struct Initialize<'info> {
     pub system_program: Program<'info, System>,
 }
 
-#[derive(Accounts)]
+#[derive(Accounts, ResolveAccounts)]
 pub struct ExampleInstruction<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
@@ -0,0 +1,61 @@
+use anchor_lang::{prelude::*, system_program};
+use example_iterative_resolution::MyAccount;
+use executor_account_resolver_svm::{
+    client::{litesvm::LiteSvmBackend, ResolverClient},
+    RESOLVER_PUBKEY_PAYER,
+};
+
+const PROGRAM_PATH: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../../target/deploy/example_iterative_resolution.so"
+);
+
+fn set_my_account(backend: &mut LiteSvmBackend, seeds: &[&[u8]], data: u8) -> Pubkey {
+    let key = Pubkey::find_program_address(seeds, &example_iterative_resolution::ID).0;
+    let mut account_data = vec![];
+    MyAccount { data }.try_serialize(&mut account_data).unwrap();
+    backend
+        .set_program_account(key, example_iterative_resolution::ID, account_data)
+        .unwrap();
+    key
+}
+
+#[test]
+#[ignore = "requires the program to be built with `anchor build`"]
+fn test_resolve_iteratively() {
+    let payer = Pubkey::new_unique();
+    let mut backend = LiteSvmBackend::new();
+    backend
+        .add_program_from_file(example_iterative_resolution::ID, PROGRAM_PATH)
+        .unwrap();
+    backend.airdrop(&payer, 1_000_000_000).unwrap();
+    let foo = set_my_account(&mut backend, &[b"foo"], 1);
+    let bar = set_my_account(&mut backend, &[b"bar", &[1]], 2);
+    let baz = set_my_account(&mut backend, &[b"baz", &[2]], 3);
+    let qux = Pubkey::find_program_address(&[b"qux", &[3]], &example_iterative_resolution::ID).0;
+
+    let groups = ResolverClient::new(backend, payer)
+        .resolve_execute_vaa_v1(&example_iterative_resolution::ID, &[])
+        .unwrap();
+
+    assert_eq!(groups.0.len(), 1);
+    assert!(groups.0[0].address_lookup_tables.is_empty());
+    assert_eq!(groups.0[0].instructions.len(), 1);
+    let instruction = &groups.0[0].instructions[0];
+    assert_eq!(instruction.program_id, example_iterative_resolution::ID);
+    assert_eq!(
+        instruction
+            .accounts
+            .iter()
+            .map(|meta| meta.pubkey)
+            .collect::<Vec<_>>(),
+        vec![
+            RESOLVER_PUBKEY_PAYER,
+            foo,
+            bar,
+            baz,
+            qux,
+            system_program::ID
+        ]
+    );
+}
@@ -1,16 +1,14 @@
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::{
     address_lookup_table,
-    entrypoint::MAX_PERMITTED_DATA_INCREASE,
     instruction::Instruction,
     program::{invoke, invoke_signed},
-    system_instruction::MAX_PERMITTED_DATA_LENGTH,
 };
 use anchor_lang::{system_program, InstructionData};
 use executor_account_resolver_svm::{
-    InstructionGroup, InstructionGroups, MissingAccounts, Resolver, RESOLVER_EXECUTE_VAA_V1,
-    RESOLVER_PUBKEY_PAYER, RESOLVER_RESULT_ACCOUNT, RESOLVER_RESULT_ACCOUNT_INIT_SIZE,
-    RESOLVER_RESULT_ACCOUNT_SEED,
+    InstructionGroup, InstructionGroups, MissingAccounts, Resolver, ResultAccountWriter,
+    RESOLVER_EXECUTE_VAA_V1, RESOLVER_PUBKEY_PAYER, RESOLVER_RESULT_ACCOUNT,
+    RESOLVER_RESULT_ACCOUNT_INIT_SIZE, RESOLVER_RESULT_ACCOUNT_SEED,
 };
 
 declare_id!("v3pcEfuzsPBGQ8Zy1jvtWq4iwugEWC2f3xgPd32eZgQ");
@@ -90,6 +88,35 @@ pub mod example_lookup_table_resolution {
         Ok(())
     }
 
+    /// Looks up every remaining account by its key, in reverse order, either with `find_account`
+    /// or with a `ResolverAccounts` index, to compare their compute unit costs.
+    /// Only built with the `bench` feature, so it is not part of the example's interface.
+    #[cfg(feature = "bench")]
+    pub fn lookup_accounts(ctx: Context<LookupAccounts>, indexed: bool) -> Result<()> {
+        use executor_account_resolver_svm::{find_account, ResolverAccounts};
+
+        let keys: Vec<Pubkey> = ctx
+            .remaining_accounts
+            .iter()
+            .rev()
+            .map(|acc| *acc.key)
+            .collect();
+        if indexed {
+            let mut accounts = ResolverAccounts::new(ctx.remaining_accounts);
+            for key in &keys {
+                require!(accounts.get(key).is_some(), MyError::InvalidAccounts);
+            }
+        } else {
+            for key in &keys {
+                require!(
+                    find_account(ctx.remaining_accounts, *key).is_some(),
+                    MyError::InvalidAccounts
+                );
+            }
+        }
+        Ok(())
+    }
+
     #[instruction(discriminator = &RESOLVER_EXECUTE_VAA_V1)]
     pub fn resolve_execute_vaa_v1<'c: 'info, 'info>(
         ctx: Context<'_, '_, 'c, 'info, Resolve>,
@@ -120,29 +147,13 @@ pub mod example_lookup_table_resolution {
             let ret_account_info = next_account_info(remaining_accounts_iter)?;
             let payer_info = next_account_info(remaining_accounts_iter)?;
             let system_program_info = next_account_info(remaining_accounts_iter)?;
-            require_eq!(ret_account_info.is_writable, true);
-            let mut ret = Account::<ExecutorAccountResolverResult>::try_from(ret_account_info)?;
-
-            // increase the size of the return account
-            let new_size = usize::min(
-                ret_account_info.data_len() + MAX_PERMITTED_DATA_INCREASE,
-                MAX_PERMITTED_DATA_LENGTH.try_into()?,
+            // the writer grows the result account as needed, funding its rent from the payer
+            let writer = ResultAccountWriter::new(
+                ctx.program_id,
+                ret_account_info,
+                payer_info,
+                system_program_info,
             );
-            let lamports_diff = Rent::get().map(|rent| {
-                rent.minimum_balance(new_size)
-                    .saturating_sub(ret_account_info.lamports())
-            })?;
-            system_program::transfer(
-                CpiContext::new(
-                    system_program_info.to_account_info(),
-                    system_program::Transfer {
-                        from: payer_info.to_account_info(),
-                        to: ret_account_info.to_account_info(),
-                    },
-                ),
-                lamports_diff,
-            )?;
-            ret_account_info.realloc(new_size, false)?;
 
             if ctx.remaining_accounts.len() == 4 {
                 // second run
@@ -154,16 +165,11 @@ pub mod example_lookup_table_resolution {
                     entries.push(Pubkey::find_program_address(&[&[n]], &ID).0);
                 }
 
-                // set the return value
-                ret.set_inner(ExecutorAccountResolverResult(Resolver::Missing(
-                    MissingAccounts {
-                        accounts: entries,
-                        address_lookup_tables: vec![lut_account.address],
-                    },
-                )));
-                ret.exit(ctx.program_id)?;
-
-                return Ok(Resolver::Account());
+                // write the return value
+                return writer.write(&Resolver::<InstructionGroups>::Missing(MissingAccounts {
+                    accounts: entries,
+                    address_lookup_tables: vec![lut_account.address],
+                }));
             } else if ctx.remaining_accounts.len() == 68 {
                 // last run
 
@@ -209,16 +215,13 @@ pub mod example_lookup_table_resolution {
                     data: instruction::ExecuteVaaV1.data(),
                 };
 
-                // set the return value
-                ret.set_inner(ExecutorAccountResolverResult(Resolver::Resolved(
-                    InstructionGroups(vec![InstructionGroup {
+                // write the return value
+                return writer.write(&Resolver::Resolved(InstructionGroups(vec![
+                    InstructionGroup {
                         instructions: vec![instruction.into()],
                         address_lookup_tables: vec![lut_account.address],
-                    }]),
-                )));
-                ret.exit(ctx.program_id)?;
-
-                return Ok(Resolver::Account());
+                    },
+                ])));
             }
         }
         err!(MyError::InvalidAccounts)
@@ -588,6 +591,10 @@ pub struct ExecuteVAAv1<'info> {
 #[derive(Accounts)]
 pub struct Resolve {}
 
+#[cfg(feature = "bench")]
+#[derive(Accounts)]
+pub struct LookupAccounts {}
+
 #[error_code]
 pub enum MyError {
     #[msg("Invalid accounts")]
@@ -0,0 +1,135 @@
+use anchor_lang::{prelude::*, system_program};
+use example_lookup_table_resolution::LUT;
+use executor_account_resolver_svm::{
+    client::{litesvm::LiteSvmBackend, ResolverClient},
+    RESOLVER_PUBKEY_PAYER, RESOLVER_RESULT_ACCOUNT, RESOLVER_RESULT_ACCOUNT_INIT_SIZE,
+    RESOLVER_RESULT_ACCOUNT_SEED,
+};
+
+const PROGRAM_PATH: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../../target/deploy/example_lookup_table_resolution.so"
+);
+
+fn dummy_account(n: u8) -> Pubkey {
+    Pubkey::find_program_address(&[&[n]], &example_lookup_table_resolution::ID).0
+}
+
+/// Seeds the state created by `initialize`.
+fn initialized_backend() -> (LiteSvmBackend, Pubkey) {
+    let program_id = example_lookup_table_resolution::ID;
+    let mut backend = LiteSvmBackend::new();
+    backend
+        .add_program_from_file(program_id, PROGRAM_PATH)
+        .unwrap();
+
+    let lut_address = Pubkey::new_unique();
+    let entries: Vec<Pubkey> = (0..128).map(dummy_account).collect();
+    backend
+        .set_address_lookup_table(lut_address, &entries)
+        .unwrap();
+
+    let (lut_key, bump) = Pubkey::find_program_address(&[b"lut"], &program_id);
+    let mut lut_data = vec![];
+    LUT {
+        bump,
+        address: lut_address,
+    }
+    .try_serialize(&mut lut_data)
+    .unwrap();
+    backend
+        .set_program_account(lut_key, program_id, lut_data)
+        .unwrap();
+
+    let result_key = Pubkey::find_program_address(&[RESOLVER_RESULT_ACCOUNT_SEED], &program_id).0;
+    let mut result_data = RESOLVER_RESULT_ACCOUNT.to_vec();
+    result_data.extend_from_slice(&[0; RESOLVER_RESULT_ACCOUNT_INIT_SIZE]);
+    backend
+        .set_program_account(result_key, program_id, result_data)
+        .unwrap();
+
+    (backend, lut_address)
+}
+
+#[test]
+#[ignore = "requires the program to be built with `anchor build`"]
+fn test_resolve_with_lookup_table_and_result_account() {
+    let payer = Pubkey::new_unique();
+    let (mut backend, lut_address) = initialized_backend();
+    backend.airdrop(&payer, 1_000_000_000).unwrap();
+
+    let groups = ResolverClient::new(backend, payer)
+        .resolve_execute_vaa_v1(&example_lookup_table_resolution::ID, &[])
+        .unwrap();
+
+    assert_eq!(groups.0.len(), 1);
+    assert_eq!(groups.0[0].address_lookup_tables, vec![lut_address]);
+    assert_eq!(groups.0[0].instructions.len(), 1);
+    let accounts: Vec<Pubkey> = groups.0[0].instructions[0]
+        .accounts
+        .iter()
+        .map(|meta| meta.pubkey)
+        .collect();
+    let mut expected = vec![RESOLVER_PUBKEY_PAYER];
+    expected.extend((64..96).map(dummy_account));
+    expected.push(system_program::ID);
+    assert_eq!(accounts, expected);
+}
+
+/// Compares the compute units consumed by looking up each of 68 remaining accounts,
+/// as in the final resolution pass, with `find_account` and with `ResolverAccounts`.
+#[cfg(feature = "bench")]
+#[test]
+#[ignore = "requires the program to be built with `anchor build -- --features bench`"]
+fn test_lookup_accounts_compute_units() {
+    use anchor_lang::{
+        solana_program::{
+            hash::Hash,
+            instruction::Instruction,
+            message::{v0, AddressLookupTableAccount, VersionedMessage},
+        },
+        InstructionData,
+    };
+
+    let payer = Pubkey::new_unique();
+    let (mut backend, lut_address) = initialized_backend();
+    backend.airdrop(&payer, 1_000_000_000).unwrap();
+    let lookup_table = AddressLookupTableAccount {
+        key: lut_address,
+        addresses: (0..128).map(dummy_account).collect(),
+    };
+
+    let compute_units = |indexed: bool| {
+        let instruction = Instruction {
+            program_id: example_lookup_table_resolution::ID,
+            accounts: (0..68)
+                .map(|n| AccountMeta::new_readonly(dummy_account(n), false))
+                .collect(),
+            data: example_lookup_table_resolution::instruction::LookupAccounts { indexed }.data(),
+        };
+        let message = v0::Message::try_compile(
+            &payer,
+            &[instruction],
+            &[lookup_table.clone()],
+            Hash::default(),
+        )
+        .unwrap();
+        let transaction = solana_transaction::versioned::VersionedTransaction {
+            signatures: vec![Default::default()],
+            message: VersionedMessage::V0(message),
+        };
+        backend
+            .svm()
+            .simulate_transaction(transaction)
+            .unwrap()
+            .meta
+            .compute_units_consumed
+    };
+    let linear = compute_units(false);
+    let indexed = compute_units(true);
+    println!("find_account: {linear} CU, ResolverAccounts: {indexed} CU");
+    assert!(
+        indexed < linear,
+        "find_account: {linear} CU, ResolverAccounts: {indexed} CU"
+    );
+}
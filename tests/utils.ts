@@ -19,11 +19,24 @@ type Instruction = {
 type InstructionGroup = {
   instructions: Instruction[];
   addressLookupTables: anchor.web3.PublicKey[];
+  computeUnitLimit?: number | null;
+  heapFrameSize?: number | null;
 };
 
+// the marker pubkey passed as the last remaining account to request the chunk
+// of a partial result starting at offset
+function resultOffsetMarker(offset: number): anchor.web3.PublicKey {
+  const bytes = Buffer.alloc(32);
+  bytes.write("result_offset_");
+  bytes.writeUInt32LE(offset, 14);
+  return new anchor.web3.PublicKey(bytes);
+}
+
 // a function that calls accountsToExecute repeatedly until it returns ok. as
 // long as it returns missing, we add the returned missing keys to
-// remainingAccounts and call accountsToExecute again
+// remainingAccounts and call accountsToExecute again. results written to an
+// account are read from the simulation's post state, partial results are
+// collected chunk by chunk, and unresolvable results stop the loop
 export async function resolveInstructions(
   provider: anchor.Provider,
   programId: anchor.web3.PublicKey,
@@ -42,6 +55,15 @@ export async function resolveInstructions(
   );
   const remainingAccounts: AccountMeta[] = [];
   const luts: AddressLookupTableAccount[] = [];
+  const resultAccount = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("executor-account-resolver:result")],
+    programId
+  )[0];
+  // the accounts whose post simulation state is requested, starting with the
+  // canonical result account
+  const resultAccounts: anchor.web3.PublicKey[] = [resultAccount];
+  // the chunks of a partial result collected so far
+  let partial = Buffer.alloc(0);
   let runs = 0;
   while (true) {
     runs++;
@@ -64,16 +86,12 @@ export async function resolveInstructions(
       recentBlockhash: blockhash,
     }).compileToV0Message(luts);
     const tx = new anchor.web3.VersionedTransaction(messageV0);
-    const resultAccount = anchor.web3.PublicKey.findProgramAddressSync(
-      [Buffer.from("executor-account-resolver:result")],
-      programId
-    )[0];
     const simulationResult =
       await program.provider.connection.simulateTransaction(tx, {
         replaceRecentBlockhash: true,
         accounts: {
           encoding: "base64",
-          addresses: [resultAccount.toString()],
+          addresses: resultAccounts.map((key) => key.toString()),
         },
       });
     console.log(simulationResult);
@@ -96,29 +114,88 @@ export async function resolveInstructions(
     const coder = IdlCoder.fieldLayout({ type: returnType }, program.idl.types);
     let result = coder.decode(returnData);
     console.log(JSON.stringify(result, undefined, 2));
-    if (result.account) {
-      if (!simulationResult.value.accounts?.[0].data[0]) {
+    if (result.account || result.accountAt) {
+      const address = result.accountAt ? result.accountAt[0] : resultAccount;
+      if (!address.equals(resultAccount) && partial.length > 0) {
+        // the chunks were written to the canonical result account, so the
+        // final one must be too
+        throw new Error(
+          `Partial result completed in ${address} rather than the result account`
+        );
+      }
+      const index = resultAccounts.findIndex((key) => key.equals(address));
+      if (index === -1) {
+        // simulate again with the account's state requested and the account
+        // writable
+        resultAccounts.push(address);
+        remainingAccounts
+          .filter((meta) => meta.pubkey.equals(address))
+          .forEach((meta) => {
+            meta.isWritable = true;
+          });
+        continue;
+      }
+      const data = simulationResult.value.accounts?.[index]?.data[0];
+      if (!data) {
         throw new Error("View expected return account data");
       }
       result = coder.decode(
-        Buffer.from(
-          simulationResult.value.accounts[0].data[0],
-          "base64"
-        ).subarray(8)
+        Buffer.concat([partial, Buffer.from(data, "base64").subarray(8)])
       );
       console.log(result);
+    } else if (result.partial) {
+      const nextOffset: number = result.partial.nextOffset;
+      const offset = partial.length;
+      if (nextOffset <= offset) {
+        throw new Error(
+          `Partial result offset ${nextOffset} does not advance past ${offset}`
+        );
+      }
+      const data = simulationResult.value.accounts?.[0]?.data[0];
+      if (!data) {
+        throw new Error("View expected return account data");
+      }
+      partial = Buffer.concat([
+        partial,
+        Buffer.from(data, "base64").subarray(8, 8 + nextOffset - offset),
+      ]);
+      // request the next chunk via the marker in the last remaining account
+      const marker = {
+        pubkey: resultOffsetMarker(nextOffset),
+        isSigner: false,
+        isWritable: false,
+      };
+      if (offset === 0) {
+        remainingAccounts.push(marker);
+      } else {
+        remainingAccounts[remainingAccounts.length - 1] = marker;
+      }
+      continue;
     }
     if (result.resolved) {
       console.log("Runs", runs);
       return result.resolved[0][0];
-    } else {
+    } else if (result.resolvedV2) {
+      console.log("Runs", runs);
+      return result.resolvedV2[0][0];
+    } else if (result.unresolvable) {
+      // the request can never be resolved, so retrying is pointless
+      throw new Error(
+        `Request is unresolvable (code ${result.unresolvable.code}): ${result.unresolvable.reason}`
+      );
+    } else if (result.missing) {
+      // a resolver which needs more accounts restarts its partial result
+      if (partial.length > 0) {
+        partial = Buffer.alloc(0);
+        remainingAccounts.pop();
+      }
       let newAccountMetas = result.missing[0].accounts.map((key) => {
         return {
           pubkey: new anchor.web3.PublicKey(key).equals(payerConst)
             ? payerWallet.publicKey
             : key,
           isSigner: false,
-          isWritable: resultAccount.equals(key) ? true : false,
+          isWritable: resultAccounts.some((resultKey) => resultKey.equals(key)),
         };
       });
       remainingAccounts.push(...newAccountMetas);
@@ -130,6 +207,8 @@ export async function resolveInstructions(
         )
       ).map((r) => r.value);
       luts.push(...newLookupTables);
+    } else {
+      throw new Error(`Unexpected result ${JSON.stringify(result)}`);
     }
   }
 }